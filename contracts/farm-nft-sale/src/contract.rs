@@ -1,9 +1,10 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
     attr, entry_point, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    QueryRequest, Response, StdError, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
+    QueryRequest, Response, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
 };
 
+use crate::error::ContractError;
 use crate::msg::{
     ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse, MerkleRootResponse,
     MigrateMsg, QueryMsg, UpdateConfigMsg,
@@ -24,7 +25,7 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let config = Config {
         admin: info.sender.to_string(),
@@ -38,7 +39,12 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::BuyPack { tool_type, stage, proof } => execute_buy_pack(deps, env, info, stage, proof, tool_type),
         ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, info, msg),
@@ -58,17 +64,18 @@ pub fn execute_register_merkle_root(
     _env: Env,
     info: MessageInfo,
     merkle_root: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
 
     // if owner set validate, otherwise unauthorized
     if info.sender != cfg.admin {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     // check merkle root length
     let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(merkle_root.to_string(), &mut root_buf).unwrap();
+    hex::decode_to_slice(merkle_root.to_string(), &mut root_buf)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
 
     let stage = LATEST_STAGE.update(deps.storage, |stage| -> StdResult<_> { Ok(stage + 1) })?;
 
@@ -88,11 +95,11 @@ pub fn claim(
     stage: u8,
     amount: &Uint128,
     proof: Vec<String>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // verify not claimed
     let claimed = CLAIM.may_load(store, (&info.sender, U8Key::from(stage)))?;
     if claimed.is_some() {
-        return Err(StdError::generic_err("Claimed"));
+        return Err(ContractError::Claimed {});
     }
 
     let merkle_root = MERKLE_ROOT.load(store, stage.into())?;
@@ -101,24 +108,24 @@ pub fn claim(
     let hash = sha2::Sha256::digest(user_input.as_bytes())
         .as_slice()
         .try_into()
-        .map_err(|_| StdError::generic_err("Wrong length"))
-        .unwrap();
+        .map_err(|_| ContractError::WrongLength {})?;
 
     let hash = proof.into_iter().try_fold(hash, |hash, p| {
         let mut proof_buf = [0; 32];
-        hex::decode_to_slice(p, &mut proof_buf).unwrap();
+        hex::decode_to_slice(p, &mut proof_buf).map_err(|_| ContractError::InvalidProof {})?;
         let mut hashes = [hash, proof_buf];
         hashes.sort_unstable();
         sha2::Sha256::digest(&hashes.concat())
             .as_slice()
             .try_into()
-            .map_err(|_| StdError::generic_err("Wrong length"))
+            .map_err(|_| ContractError::WrongLength {})
     })?;
 
     let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(merkle_root, &mut root_buf).unwrap();
+    hex::decode_to_slice(merkle_root, &mut root_buf)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
     if root_buf != hash {
-        return Err(StdError::generic_err("Verification Failed"));
+        return Err(ContractError::VerificationFailed {});
     }
 
     // Update claim index to the current stage
@@ -130,10 +137,10 @@ fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
     msg: UpdateConfigMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
     if config.admin != info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
     if msg.admin.is_some() {
         config.admin = msg.admin.unwrap();
@@ -162,7 +169,7 @@ pub fn execute_buy_pack(
     stage: u8,
     proof: Vec<String>,
     tool_type: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     assert_sent_native_token_balance(
         deps.storage,
@@ -253,7 +260,7 @@ pub fn assert_sent_native_token_balance(
     proof: Vec<String>,
     pack_rate: Uint128,
     ust_address: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let coin = message_info.funds.iter().find(|x| x.denom == ust_address);
 
     if coin.is_some() {
@@ -268,14 +275,17 @@ pub fn assert_sent_native_token_balance(
             // )?
         )
         } else {
-            Err(StdError::generic_err("Please provide required coins"))
+            Err(ContractError::IncorrectPayment {
+                expected: pack_rate,
+                received: coin.unwrap().amount,
+            })
         }
     } else {
-        Err(StdError::generic_err("Invalid Coins provided"))
+        Err(ContractError::InvalidCoins { denom: ust_address })
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     Ok(Response::default())
 }
@@ -0,0 +1,35 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Already claimed for this stage")]
+    Claimed {},
+
+    #[error("Wrong hash length")]
+    WrongLength {},
+
+    #[error("Merkle proof verification failed")]
+    VerificationFailed {},
+
+    #[error("Merkle root must be a 32 byte hex string")]
+    InvalidMerkleRoot {},
+
+    #[error("Merkle proof entries must be 32 byte hex strings")]
+    InvalidProof {},
+
+    #[error("No {denom} coins provided")]
+    InvalidCoins { denom: String },
+
+    #[error("Pack costs {expected}, received {received}")]
+    IncorrectPayment {
+        expected: Uint128,
+        received: Uint128,
+    },
+}
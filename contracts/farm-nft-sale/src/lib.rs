@@ -1,3 +1,8 @@
 pub mod contract;
+mod error;
 pub mod msg;
 pub mod state;
+#[cfg(test)]
+mod testing;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,81 @@
+use crate::contract::{claim, execute, instantiate};
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{DepsMut, Uint128};
+
+mod tests {
+    use super::*;
+
+    const ROOT: &str = "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88";
+
+    fn setup_contract(deps: DepsMut) {
+        let init_msg = InstantiateMsg {
+            admin: "admin".to_string(),
+            ust_address: "uusd".to_string(),
+            reserve_addr: "reserve_address".to_string(),
+            pack_rate: Uint128::from(100u128),
+            nft_contract_address: "nft_address".to_string(),
+        };
+        instantiate(deps, mock_env(), mock_info("admin", &[]), init_msg).unwrap();
+    }
+
+    #[test]
+    fn test_register_invalid_merkle_root() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        for merkle_root in ["not hex", "b45c1ea2"] {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("admin", &[]),
+                ExecuteMsg::RegisterMerkleRoot {
+                    merkle_root: merkle_root.to_string(),
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidMerkleRoot {});
+        }
+    }
+
+    #[test]
+    fn test_claim_with_invalid_proof() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::RegisterMerkleRoot {
+                merkle_root: ROOT.to_string(),
+            },
+        )
+        .unwrap();
+
+        let info = mock_info("user1", &[]);
+        let amount = Uint128::from(100u128);
+        for proof in ["zz", "b45c1ea2"] {
+            let err = claim(
+                deps.as_mut().storage,
+                &info,
+                1,
+                &amount,
+                vec![proof.to_string()],
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidProof {});
+        }
+
+        // a well formed proof that does not lead to the root is still rejected
+        let err = claim(
+            deps.as_mut().storage,
+            &info,
+            1,
+            &amount,
+            vec![ROOT.to_string()],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed {});
+    }
+}
@@ -1,32 +1,53 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Order, QueryRequest, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
-    WasmQuery,
+    coins, entry_point, from_binary, to_binary, Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps,
+    DepsMut, Env, Event, MessageInfo, Order, QueryRequest, Response, StdError, StdResult, Storage,
+    Uint128, WasmMsg, WasmQuery,
 };
 use cw0::maybe_addr;
 use cw2::set_contract_version;
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 use cw721::{
-    ContractInfoResponse, Cw721ReceiveMsg, Expiration, NumTokensResponse, OwnerOfResponse,
-    TokensResponse,
+    ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Cw721ReceiveMsg, Expiration,
+    NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
 };
-use cw_storage_plus::Bound;
+use cw721_base::msg::MinterResponse;
+use cw_storage_plus::{Bound, Map, PrimaryKey, U64Key};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::collections::HashSet;
 
+use crate::error::ContractError;
 use crate::mint::{
     execute_batch_mint, execute_mint, execute_mint_common_nft, execute_mint_upgraded_nft,
 };
 use crate::msg::{
-    AllNftInfoResponse, Cw20HookMsg, Cw721HookMsg, ExecuteMsg, Extension, InstantiateMsg,
-    MigrateMsg, NftInfoResponse, QueryMsg, ToolTemplateMsg, UpdateConfigMsg,
+    AllNftInfoResponse, BurnedItemInfo, BurnedItemsResponse, Cw20HookMsg, Cw721HookMsg,
+    DistributionResponse, EmissionBudgetResponse, ExecuteMsg, Extension, InstantiateMsg,
+    ItemAmount, ItemDistributionInfo, ItemEmissionBudget, ItemTokenInfo, ItemTokensResponse,
+    MigrateMsg, NftInfoResponse, PoolItemStatus, PoolStatusResponse, QueryMsg,
+    RarityLadderResponse, RarityMultiplierInfo, RarityMultipliersResponse, RarityStep,
+    RepairingFeeInfo, RepairingFeesResponse, ReserveSweepInfo, ReserveSweepsResponse,
+    RewardRateResponse, RewardTokenInfo, RewardTokensResponse, StakeholderBalance,
+    StakeholderBalancesResponse, ToolTemplateInfo, ToolTemplateMsg, ToolTemplatesResponse,
+    ToolTypesResponse, UpdateConfigMsg, UpdateEmissionConfigMsg, UpdateEnergyConfigMsg,
+    UserEnergyInfoResponse, WithdrawAllowanceResponse,
 };
 use crate::state::{
-    distribute_amount, num_tokens, tokens, Approval, Config, RewardToken, TokenInfo, ToolTemplate,
-    CONFIG, CONTRACT_INFO, GAME_DEV_TOKENS_NAME, ITEM_TOKEN_MAPPING, LAST_GEN_TOKEN_ID, OPERATORS,
-    RARITY_TYPES, REPAIRING_FEE, REPAIR_KIT_KEYWORD, REWARD_TOKEN, TOKEN_COUNT, TOKEN_ITEM_MAPPING,
-    TOOL_PACK_SET, TOOL_SET_MAP, TOOL_TEMPLATE_MAP, TOOL_TYPE_NAMES, USER_ENERGY_LEVEL,
-    USER_ITEM_AMOUNT, USER_REPAIR_KITS, USER_STAKED_INFO,  
+    add_amount_in_item_address, default_distribution, distribute_amount, load_emission_usage,
+    load_energy, num_tokens, parse_pair_key, rarity_multiplier, registered_items, save_energy,
+    save_item_amount, tokens, Approval, Config, DistributionRecipient, DistributionShare,
+    EmissionConfig, EnergyConfig, ItemInfo, RarityMultiplier, RateSchedule, ReserveSweep,
+    RewardOutput, RewardToken, TokenInfo, ToolTemplate, WithdrawPolicy, BPS_DENOMINATOR,
+    BURNED_ITEMS, CONFIG, CONTRACT_INFO, DEFAULT_ITEM_DECIMALS, DISTRIBUTION, EMISSION_CONFIG,
+    EMISSION_USAGE, ENERGY_CONFIG, ITEMS, ITEM_DISTRIBUTION, ITEM_SUPPLY, LAST_GEN_TOKEN_ID,
+    LEGACY_GAME_DEV_TOKENS_NAME, LEGACY_ITEM_TOKEN_MAPPING, LEGACY_REWARD_TOKEN,
+    LEGACY_TEMPLATE_ITEMS, LEGACY_USER_ITEM_AMOUNT, LEGACY_USER_REPAIR_KITS, OPERATORS,
+    PACK_KEYWORD, PENDING_BURNS, RARITY_MULTIPLIERS, RARITY_TYPES, REPAIRING_FEE,
+    REPAIR_KIT_KEYWORD, RESERVE_SWEEPS, RESERVE_SWEEP_COUNT, REWARD_TOKEN, STAKED_TOOL_COUNT,
+    TOKEN_COUNT, TOKEN_ITEM_MAPPING, TOOL_PACK_SET, TOOL_SET_MAP, TOOL_TEMPLATE_MAP,
+    TOOL_TYPE_NAMES, USER_ENERGY_LEVEL, USER_ITEM_AMOUNT, USER_REPAIR_KITS, USER_STAKED_INFO,
+    USER_WITHDRAWALS, WITHDRAW_POLICIES,
 };
 
 const CONTRACT_NAME: &str = "crates.io:loop-nft";
@@ -34,6 +55,7 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 30;
+const SECONDS_PER_DAY: u64 = 86_400;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -41,7 +63,7 @@ pub fn instantiate(
     env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let contract_info = ContractInfoResponse {
         name: msg.name,
@@ -58,19 +80,28 @@ pub fn instantiate(
         durability_start_time: env.block.time.seconds() + msg.durability_from_start_time,
         reserve_addr: msg.reserve_addr,
         repair_kit_waiting_time: msg.repair_kit_waiting_time,
+        pool_floor: Uint128::zero(),
+        partial_claims: false,
+        transfer_fee_bps: 0,
     };
 
     CONTRACT_INFO.save(deps.storage, &contract_info)?;
     CONFIG.save(deps.storage, &config)?;
+    ENERGY_CONFIG.save(deps.storage, &EnergyConfig::default())?;
+    DISTRIBUTION.save(deps.storage, &default_distribution())?;
+    EMISSION_CONFIG.save(deps.storage, &EmissionConfig::default())?;
     TOOL_TYPE_NAMES.save(deps.storage, &vec![])?;
     LAST_GEN_TOKEN_ID.save(deps.storage, &0u64)?;
-    let game_dev_token_set = Vec::<String>::new();
-    GAME_DEV_TOKENS_NAME.save(deps.storage, &game_dev_token_set)?;
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
         ExecuteMsg::TransferNft {
@@ -83,23 +114,44 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             token_id,
             msg,
         } => execute_send_nft(deps, env, info, contract, token_id, msg),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => execute_approve(deps, env, info, spender, token_id, expires),
+        ExecuteMsg::Revoke { spender, token_id } => {
+            execute_revoke(deps, env, info, spender, token_id)
+        }
+        ExecuteMsg::ApproveAll { operator, expires } => {
+            execute_approve_all(deps, env, info, operator, expires)
+        }
+        ExecuteMsg::RevokeAll { operator } => execute_revoke_all(deps, env, info, operator),
         ExecuteMsg::ReceiveNft(msg) => execute_receive_cw721(deps, env, info, msg),
         ExecuteMsg::ClaimReward { token_id } => execute_claim_reward(deps, env, info, token_id),
+        ExecuteMsg::ClaimRewards { token_ids } => execute_claim_rewards(deps, env, info, token_ids),
         ExecuteMsg::Unstake { token_id } => execute_unstake(deps, env, info, token_id),
+        ExecuteMsg::UnstakeMany { token_ids } => execute_unstake_many(deps, env, info, token_ids),
         ExecuteMsg::Receive(msg) => execute_receive_cw20(deps, env, info, msg),
         ExecuteMsg::AddRewardToken {
             item_name,
-            tool_name,
+            tool_type,
+            rarity,
             mining_rate,
             mining_waiting_time,
+            schedule,
+            extra_outputs,
         } => execute_add_reward_token(
             deps,
-            env,
             info,
-            item_name,
-            tool_name,
-            mining_rate,
-            mining_waiting_time,
+            tool_type,
+            rarity,
+            RewardToken {
+                item_name,
+                mining_rate,
+                mining_waiting_time,
+                schedule,
+                extra_outputs: extra_outputs.unwrap_or_default(),
+            },
         ),
         ExecuteMsg::AddToolTypeNames { tool_type } => {
             execute_add_tool_type_names(deps, env, info, tool_type)
@@ -108,7 +160,8 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::AddItemToken {
             item_name,
             item_token_addr,
-        } => execute_add_item_token(deps, env, info, item_name, item_token_addr),
+            decimals,
+        } => execute_add_item_token(deps, info, item_name, item_token_addr, decimals),
         ExecuteMsg::RefillEnergy { food_item_amount } => {
             execute_refill_energy(deps, env, info, food_item_amount)
         }
@@ -116,6 +169,20 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::Withdraw { item_name, amount } => {
             execute_withdraw(deps, env, info, item_name, amount)
         }
+        ExecuteMsg::TransferItem {
+            recipient,
+            item_name,
+            amount,
+        } => execute_send_item_batch(
+            deps,
+            env,
+            info,
+            recipient,
+            vec![ItemAmount { item_name, amount }],
+        ),
+        ExecuteMsg::SendItemBatch { recipient, items } => {
+            execute_send_item_batch(deps, env, info, recipient, items)
+        }
         ExecuteMsg::AddToolTemplate(msg) => execute_add_tool_template(deps, env, info, msg),
         ExecuteMsg::MintCommonNft { tool_type } => {
             execute_mint_common_nft(deps, env, info, tool_type)
@@ -125,15 +192,51 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             execute_mint_upgraded_nft(deps, env, info, token_ids)
         }
         ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, info, msg),
+        ExecuteMsg::UpdateEnergyConfig(msg) => execute_update_energy_config(deps, info, msg),
+        ExecuteMsg::UpdateEmissionConfig(msg) => execute_update_emission_config(deps, info, msg),
+        ExecuteMsg::SetRarityMultiplier { rarity, multiplier } => {
+            execute_set_rarity_multiplier(deps, info, rarity, multiplier)
+        }
+        ExecuteMsg::SetWithdrawPolicy { item_name, policy } => {
+            execute_set_withdraw_policy(deps, info, item_name, policy)
+        }
+        ExecuteMsg::RemoveWithdrawPolicy { item_name } => {
+            execute_remove_withdraw_policy(deps, info, item_name)
+        }
+        ExecuteMsg::FlushBurns {} => execute_flush_burns(deps),
+        ExecuteMsg::PayoutStakeholders { items } => {
+            execute_payout_stakeholders(deps, env, info, items)
+        }
+        ExecuteMsg::SetDistribution { item_name, shares } => {
+            execute_set_distribution(deps, info, item_name, shares)
+        }
+        ExecuteMsg::RemoveItemDistribution { item_name } => {
+            execute_remove_item_distribution(deps, info, item_name)
+        }
 
-        ExecuteMsg::TransferReserveAmount {} => execute_transfer_reserve_amount(deps, info, env),
+        ExecuteMsg::TransferReserveAmount { items, amount } => {
+            execute_transfer_reserve_amount(deps, info, env, items, amount)
+        }
 
         ExecuteMsg::TransferToolPack {
             recipient,
             tool_type,
         } => execute_transfer_tool_pack(deps, info, env, recipient, tool_type),
 
-        ExecuteMsg::AddItemName { item_name } => execute_adding_item(deps, info, item_name),
+        ExecuteMsg::AddItem {
+            item_name,
+            item_token_addr,
+            decimals,
+        } => execute_add_item(deps, info, item_name, item_token_addr, decimals, false),
+        ExecuteMsg::AddNativeItem {
+            item_name,
+            denom,
+            decimals,
+        } => execute_add_item(deps, info, item_name, denom, decimals, true),
+        ExecuteMsg::Deposit {} => execute_deposit_native(deps, info),
+        ExecuteMsg::DisableItem { item_name } => execute_disable_item(deps, info, item_name),
+        ExecuteMsg::EnableItem { item_name } => execute_enable_item(deps, info, item_name),
+        ExecuteMsg::RemoveItem { item_name } => execute_remove_item(deps, info, item_name),
 
         ExecuteMsg::AddRaritiesMapping {
             tool_type,
@@ -148,7 +251,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
 
         ExecuteMsg::AddRepairingFee { rarity, fee } => {
             execute_add_repairing_fee(deps, info, rarity, fee)
-        },
+        }
     }
 }
 
@@ -158,18 +261,16 @@ fn execute_transfer_tool_pack(
     env: Env,
     recipient: String,
     tool_type: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let mut tool_pack_set =
         if let Some(tool_pack_set) = TOOL_PACK_SET.may_load(deps.storage, tool_type.to_string())? {
             tool_pack_set
         } else {
-            return Err(StdError::generic_err("No tool pack available to transfer"));
+            return Err(ContractError::NoPackAvailable { tool_type });
         };
 
     if tool_pack_set.is_empty() {
-        return Err(StdError::generic_err(
-            "No tool pack list available to transfer",
-        ));
+        return Err(ContractError::NoPackAvailable { tool_type });
     }
     let token_id = tool_pack_set.swap_remove(0);
     //let token = tokens().load(deps.storage, &token_id)?;
@@ -186,10 +287,10 @@ fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
     msg: UpdateConfigMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
     if config.minter != info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
     if msg.team_addr.is_some() {
         config.team_addr = msg.team_addr.unwrap();
@@ -212,21 +313,205 @@ fn execute_update_config(
     if msg.reserve_addr.is_some() {
         config.reserve_addr = msg.reserve_addr.unwrap();
     }
+    if msg.pool_floor.is_some() {
+        config.pool_floor = msg.pool_floor.unwrap();
+    }
+    if msg.partial_claims.is_some() {
+        config.partial_claims = msg.partial_claims.unwrap();
+    }
+    if let Some(transfer_fee_bps) = msg.transfer_fee_bps {
+        if transfer_fee_bps > BPS_DENOMINATOR {
+            return Err(ContractError::InvalidFee {
+                bps: transfer_fee_bps,
+            });
+        }
+        config.transfer_fee_bps = transfer_fee_bps;
+    }
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new()
         .add_attribute("action", "update config")
         .add_attribute("sender", info.sender))
 }
 
+fn execute_update_energy_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: UpdateEnergyConfigMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut energy_config = ENERGY_CONFIG.load(deps.storage)?;
+    if let Some(starter_energy) = msg.starter_energy {
+        energy_config.starter_energy = starter_energy;
+    }
+    if let Some(claim_cost) = msg.claim_cost {
+        energy_config.claim_cost = claim_cost;
+    }
+    if let Some(claim_cost_overrides) = msg.claim_cost_overrides {
+        energy_config.claim_cost_overrides = claim_cost_overrides;
+    }
+    if let Some(refill_item) = msg.refill_item {
+        energy_config.refill_item = refill_item;
+    }
+    if let Some(refill_ratio) = msg.refill_ratio {
+        energy_config.refill_ratio = refill_ratio;
+    }
+    if let Some(max_energy) = msg.max_energy {
+        energy_config.max_energy = if max_energy.is_zero() {
+            None
+        } else {
+            Some(max_energy)
+        };
+    }
+    if let Some(regen_amount) = msg.regen_amount {
+        energy_config.regen_amount = regen_amount;
+    }
+    if let Some(regen_interval) = msg.regen_interval {
+        energy_config.regen_interval = regen_interval;
+    }
+    ENERGY_CONFIG.save(deps.storage, &energy_config)?;
+    Ok(Response::new()
+        .add_attribute("action", "update energy config")
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_update_emission_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: UpdateEmissionConfigMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut emission_config = EMISSION_CONFIG.load(deps.storage)?;
+    if let Some(epoch_length) = msg.epoch_length {
+        emission_config.epoch_length = epoch_length;
+    }
+    if let Some(global_cap) = msg.global_cap {
+        emission_config.global_cap = if global_cap.is_zero() {
+            None
+        } else {
+            Some(global_cap)
+        };
+    }
+    if let Some(item_caps) = msg.item_caps {
+        emission_config.item_caps = item_caps;
+    }
+    EMISSION_CONFIG.save(deps.storage, &emission_config)?;
+    Ok(Response::new()
+        .add_attribute("action", "update emission config")
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_set_rarity_multiplier(
+    deps: DepsMut,
+    info: MessageInfo,
+    rarity: String,
+    multiplier: RarityMultiplier,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    RARITY_MULTIPLIERS.save(deps.storage, rarity.to_string(), &multiplier)?;
+    Ok(Response::new()
+        .add_attribute("action", "set rarity multiplier")
+        .add_attribute("rarity", rarity))
+}
+
+fn execute_set_withdraw_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+    policy: WithdrawPolicy,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !policy.is_valid() {
+        return Err(ContractError::InvalidWithdrawPolicy {});
+    }
+    WITHDRAW_POLICIES.save(deps.storage, item_name.to_string(), &policy)?;
+    Ok(Response::new()
+        .add_attribute("action", "set withdraw policy")
+        .add_attribute("item_name", item_name))
+}
+
+fn execute_remove_withdraw_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    WITHDRAW_POLICIES.remove(deps.storage, item_name.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "remove withdraw policy")
+        .add_attribute("item_name", item_name))
+}
+
+fn execute_set_distribution(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: Option<String>,
+    shares: Vec<DistributionShare>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let total_bps: u64 = shares.iter().map(|share| share.bps).sum();
+    if total_bps != BPS_DENOMINATOR {
+        return Err(ContractError::InvalidDistribution { total_bps });
+    }
+    for share in shares.iter() {
+        if let DistributionRecipient::Address(addr) = &share.recipient {
+            deps.api.addr_validate(addr)?;
+        }
+    }
+    let response = Response::new()
+        .add_attribute("action", "set distribution")
+        .add_attribute("sender", info.sender);
+    if let Some(item_name) = item_name {
+        ITEM_DISTRIBUTION.save(deps.storage, item_name.to_string(), &shares)?;
+        Ok(response.add_attribute("item_name", item_name))
+    } else {
+        DISTRIBUTION.save(deps.storage, &shares)?;
+        Ok(response)
+    }
+}
+
+fn execute_remove_item_distribution(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    ITEM_DISTRIBUTION.remove(deps.storage, item_name.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "remove item distribution")
+        .add_attribute("sender", info.sender)
+        .add_attribute("item_name", item_name))
+}
+
 fn execute_add_rarities_mapping(
     deps: DepsMut,
     info: MessageInfo,
     tool_type: String,
     upgraded_tool_type: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if config.minter != info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
     RARITY_TYPES.save(deps.storage, tool_type.to_string(), &upgraded_tool_type)?;
     Ok(Response::new()
@@ -235,16 +520,180 @@ fn execute_add_rarities_mapping(
         .add_attribute("upgraded tool type", upgraded_tool_type))
 }
 
-fn execute_adding_item(deps: DepsMut, info: MessageInfo, item_name: String) -> StdResult<Response> {
+/// adds a game dev token and registers its cw20 token or, for native items, its bank denom.
+/// An item can only be added once
+fn execute_add_item(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+    item_token_addr: String,
+    decimals: Option<u8>,
+    native: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if ITEMS
+        .may_load(deps.storage, item_name.to_string())?
+        .is_some()
+    {
+        return Err(ContractError::DuplicateItem { item: item_name });
+    }
+    // a token can only back one item
+    if let Some(mapped_item) =
+        TOKEN_ITEM_MAPPING.may_load(deps.storage, item_token_addr.to_string())?
+    {
+        return Err(ContractError::DuplicateItem { item: mapped_item });
+    }
+    let item = ItemInfo {
+        token_addr: item_token_addr.to_string(),
+        decimals: decimals.unwrap_or(DEFAULT_ITEM_DECIMALS),
+        enabled: true,
+        native,
+    };
+    ITEMS.save(deps.storage, item_name.to_string(), &item)?;
+    TOKEN_ITEM_MAPPING.save(deps.storage, item_token_addr.to_string(), &item_name)?;
+    Ok(Response::new()
+        .add_attribute("action", "add item")
+        .add_event(
+            Event::new("item_added")
+                .add_attribute("item_name", item_name)
+                .add_attribute("token_addr", item_token_addr)
+                .add_attribute("decimals", item.decimals.to_string())
+                .add_attribute("native", native.to_string()),
+        ))
+}
+
+/// disables an item, balances can still be withdrawn and existing templates still charge it
+fn execute_disable_item(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+        item
+    } else {
+        return Err(ContractError::ItemTokenNotFound { item: item_name });
+    };
+    if !item.enabled {
+        return Err(ContractError::ItemDisabled { item: item_name });
+    }
+    item.enabled = false;
+    ITEMS.save(deps.storage, item_name.to_string(), &item)?;
+    Ok(Response::new()
+        .add_attribute("action", "disable item")
+        .add_event(Event::new("item_disabled").add_attribute("item_name", item_name)))
+}
+
+/// enables a disabled item again so templates and rewards can use it
+fn execute_enable_item(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if config.minter != info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
-    let mut game_dev_token_set = GAME_DEV_TOKENS_NAME.load(deps.storage)?;
-    game_dev_token_set.push(item_name.to_string());
+    let mut item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+        item
+    } else {
+        return Err(ContractError::ItemTokenNotFound { item: item_name });
+    };
+    item.enabled = true;
+    ITEMS.save(deps.storage, item_name.to_string(), &item)?;
     Ok(Response::new()
-        .add_attribute("action", "item added")
-        .add_attribute("item", item_name))
+        .add_attribute("action", "enable item")
+        .add_event(Event::new("item_enabled").add_attribute("item_name", item_name)))
+}
+
+/// removes an item from the registry once nothing refers to it
+fn execute_remove_item(
+    deps: DepsMut,
+    info: MessageInfo,
+    item_name: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+        item
+    } else {
+        return Err(ContractError::ItemTokenNotFound { item: item_name });
+    };
+    if let Some(used_by) = item_used_by(deps.storage, &item_name)? {
+        return Err(ContractError::ItemInUse {
+            item: item_name,
+            used_by,
+        });
+    }
+    TOKEN_ITEM_MAPPING.remove(deps.storage, item.token_addr);
+    ITEMS.remove(deps.storage, item_name.to_string());
+    ITEM_DISTRIBUTION.remove(deps.storage, item_name.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "remove item")
+        .add_event(Event::new("item_removed").add_attribute("item_name", item_name)))
+}
+
+/// first tool template, reward token, game setting or balance found using the item
+fn item_used_by(storage: &dyn Storage, item_name: &str) -> StdResult<Option<String>> {
+    if ENERGY_CONFIG.load(storage)?.refill_item == item_name {
+        return Ok(Some("energy refill".to_string()));
+    }
+    if WITHDRAW_POLICIES
+        .may_load(storage, item_name.to_string())?
+        .is_some()
+    {
+        return Ok(Some("withdraw policy".to_string()));
+    }
+    if !PENDING_BURNS
+        .may_load(storage, item_name)?
+        .unwrap_or_default()
+        .is_zero()
+    {
+        return Ok(Some("pending burns".to_string()));
+    }
+    let templates = TOOL_TEMPLATE_MAP
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, ToolTemplate)>>>()?;
+    for (template_key, template) in templates {
+        if template
+            .required_items
+            .iter()
+            .any(|(name, _)| name == item_name)
+        {
+            let template_key = String::from_utf8(template_key).map_err(StdError::invalid_utf8)?;
+            return Ok(Some(format!("template {}", template_key)));
+        }
+    }
+    let reward_tokens = REWARD_TOKEN
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, RewardToken)>>>()?;
+    for (key, reward_token) in reward_tokens {
+        if reward_token.item_name == item_name
+            || reward_token
+                .extra_outputs
+                .iter()
+                .any(|output| output.item_name == item_name)
+        {
+            let (tool_type, rarity) = parse_pair_key(&key)?;
+            return Ok(Some(format!("reward {} {}", tool_type, rarity)));
+        }
+    }
+    if !ITEM_SUPPLY
+        .may_load(storage, item_name)?
+        .unwrap_or_default()
+        .is_zero()
+    {
+        return Ok(Some("balances".to_string()));
+    }
+    Ok(None)
 }
 
 fn execute_add_repairing_fee(
@@ -252,11 +701,11 @@ fn execute_add_repairing_fee(
     info: MessageInfo,
     rarity: String,
     fee: Uint128,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     if config.minter == info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     REPAIRING_FEE.save(deps.storage, rarity.to_string(), &fee)?;
@@ -267,57 +716,193 @@ fn execute_add_repairing_fee(
         .add_attribute("fee", fee))
 }
 
+/// burns the item tokens of the pending burn shares, anyone can trigger it
+fn execute_flush_burns(deps: DepsMut) -> Result<Response, ContractError> {
+    let burn_balances = PENDING_BURNS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| -> StdResult<(String, Uint128)> {
+            let (k, amount) = item?;
+            let item_name = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+            Ok((item_name, amount))
+        })
+        .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+
+    let mut responses: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![];
+    for (item_name, amount) in burn_balances {
+        if amount.is_zero() {
+            continue;
+        }
+        // items without a token contract stay credited until one is mapped
+        let item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+            item
+        } else {
+            continue;
+        };
+        responses.push(item_burn_msg(&item, amount)?);
+        PENDING_BURNS.remove(deps.storage, &item_name);
+        let burned = BURNED_ITEMS
+            .may_load(deps.storage, item_name.to_string())?
+            .unwrap_or_default();
+        BURNED_ITEMS.save(deps.storage, item_name.to_string(), &(burned + amount))?;
+        attributes.push((item_name, amount.to_string()));
+    }
+    Ok(Response::new()
+        .add_messages(responses)
+        .add_attribute("action", "flush burns")
+        .add_attributes(attributes))
+}
+
+/// accrued item balances of team, market and legal, an address is listed once
+/// even if it holds several roles
+fn stakeholder_balances(
+    storage: &dyn Storage,
+    env: &Env,
+    items: Option<Vec<String>>,
+) -> StdResult<Vec<(Addr, Vec<(String, Uint128)>)>> {
+    let config = CONFIG.load(storage)?;
+    let items = match items {
+        Some(items) => items,
+        None => registered_items(storage)?,
+    };
+    let mut balances: Vec<(Addr, Vec<(String, Uint128)>)> = vec![];
+    for recipient in [
+        DistributionRecipient::Team,
+        DistributionRecipient::Market,
+        DistributionRecipient::Legal,
+    ] {
+        let address = recipient.address(&config, env);
+        if balances.iter().any(|(addr, _)| *addr == address) {
+            continue;
+        }
+        let mut item_balances = vec![];
+        for item_name in items.iter() {
+            let amount = USER_ITEM_AMOUNT
+                .may_load(storage, (&address, item_name.as_str()))?
+                .unwrap_or_default();
+            if !amount.is_zero() {
+                item_balances.push((item_name.to_string(), amount));
+            }
+        }
+        balances.push((address, item_balances));
+    }
+    Ok(balances)
+}
+
+/// pays the accrued item balances of team, market and legal out as item tokens
+fn execute_payout_stakeholders(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    items: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut responses: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![];
+    for (address, item_balances) in stakeholder_balances(deps.storage, &env, items)? {
+        for (item_name, amount) in item_balances {
+            let item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+                item
+            } else {
+                return Err(ContractError::ItemTokenNotFound { item: item_name });
+            };
+            responses.push(item_transfer_msg(&item, address.as_str(), amount)?);
+            save_item_amount(deps.storage, &address, &item_name, Uint128::zero())?;
+            attributes.push((format!("{}/{}", address, item_name), amount.to_string()));
+        }
+    }
+    Ok(Response::new()
+        .add_messages(responses)
+        .add_attribute("action", "payout stakeholders")
+        .add_attributes(attributes))
+}
+
 /// to transfer reserve amount of contract pool to withdraw
 fn execute_transfer_reserve_amount(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
-) -> StdResult<Response> {
+    items: Option<Vec<String>>,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if config.minter != info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
+    let items = match items {
+        Some(items) => items,
+        None => registered_items(deps.storage)?,
+    };
     let mut responses: Vec<CosmosMsg> = vec![];
-    let game_dev_token_set = GAME_DEV_TOKENS_NAME.load(deps.storage)?;
+    let mut swept_items = vec![];
     // iterating over dev token to get the reserve amount from all of them
-    for game_dev_token_name in game_dev_token_set {
-        let mut user_address = env.contract.address.to_string();
-        user_address.push_str(&game_dev_token_name.to_string());
-        if let Some(contract_pool_amount) =
-            USER_ITEM_AMOUNT.may_load(deps.storage, user_address.to_string())?
-        {
-            let token_addr = if let Some(token_addr) =
-                ITEM_TOKEN_MAPPING.may_load(deps.storage, game_dev_token_name.to_string())?
-            {
-                token_addr
+    for game_dev_token_name in items {
+        let contract_pool_amount = USER_ITEM_AMOUNT
+            .may_load(
+                deps.storage,
+                (&env.contract.address, game_dev_token_name.as_str()),
+            )?
+            .unwrap_or_default();
+        // the floor stays in the pool so claims can still be paid
+        let available = contract_pool_amount.saturating_sub(config.pool_floor);
+        let sweep_amount = match amount {
+            Some(amount) => amount.min(available),
+            None => available,
+        };
+        if sweep_amount.is_zero() {
+            continue;
+        }
+        let item =
+            if let Some(item) = ITEMS.may_load(deps.storage, game_dev_token_name.to_string())? {
+                item
             } else {
-                return Err(StdError::generic_err("No Item token found"));
+                return Err(ContractError::ItemTokenNotFound {
+                    item: game_dev_token_name,
+                });
             };
-            // transfering contract pool to reserve addr
-            responses.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: token_addr,
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: config.reserve_addr.to_string(),
-                    amount: contract_pool_amount,
-                })
-                .unwrap(),
-                funds: vec![],
-            }));
-            contract_pool_amount
-        } else {
-            Uint128::zero()
-        };
+        // transfering contract pool to reserve addr
+        responses.push(item_transfer_msg(
+            &item,
+            &config.reserve_addr,
+            sweep_amount,
+        )?);
         //updating amount in map
-        USER_ITEM_AMOUNT.save(
+        save_item_amount(
             deps.storage,
-            game_dev_token_name.to_string(),
-            &Uint128::zero(),
+            &env.contract.address,
+            &game_dev_token_name,
+            contract_pool_amount - sweep_amount,
         )?;
+        swept_items.push((game_dev_token_name, sweep_amount));
     }
-    Ok(Response::new()
-        .add_messages(responses)
+    let response = Response::new()
         .add_attribute("action", "transfer reserve amount")
-        .add_attribute("sender", info.sender))
+        .add_attribute("sender", info.sender);
+    // nothing above the floor, no sweep to record
+    if swept_items.is_empty() {
+        return Ok(response);
+    }
+
+    let sweep_id = RESERVE_SWEEP_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    RESERVE_SWEEP_COUNT.save(deps.storage, &sweep_id)?;
+    RESERVE_SWEEPS.save(
+        deps.storage,
+        U64Key::from(sweep_id),
+        &ReserveSweep {
+            swept_at: env.block.time.seconds(),
+            recipient: config.reserve_addr,
+            items: swept_items,
+        },
+    )?;
+    Ok(response
+        .add_messages(responses)
+        .add_attribute("sweep_id", sweep_id.to_string()))
 }
 
 /// adding tool template/snapshot in the contract
@@ -326,34 +911,48 @@ fn execute_add_tool_template(
     _env: Env,
     info: MessageInfo,
     msg: ToolTemplateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if info.sender != config.minter {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+    // every tool mines, only packs and repair kits go without a reward
+    if !msg.rarity.eq_ignore_ascii_case(PACK_KEYWORD)
+        && !msg.rarity.eq_ignore_ascii_case(REPAIR_KIT_KEYWORD)
+        && REWARD_TOKEN
+            .may_load(deps.storage, (msg.tool_type.as_str(), msg.rarity.as_str()))?
+            .is_none()
+    {
+        return Err(ContractError::RewardTokenNotFound {
+            tool_type: msg.tool_type,
+            rarity: msg.rarity,
+        });
+    }
+    let mut required_items: Vec<(String, Uint128)> = vec![];
+    for (item_name, amount) in msg.required_items {
+        if required_items.iter().any(|(name, _)| *name == item_name) {
+            return Err(ContractError::DuplicateItem { item: item_name });
+        }
+        match ITEMS.may_load(deps.storage, item_name.to_string())? {
+            Some(item) if item.enabled => {}
+            Some(_) => return Err(ContractError::ItemDisabled { item: item_name }),
+            None => return Err(ContractError::ItemTokenNotFound { item: item_name }),
+        }
+        if !amount.is_zero() {
+            required_items.push((item_name, amount));
+        }
     }
-    let mut tool_template = ToolTemplate {
+    let tool_template = ToolTemplate {
         name: msg.name,
         description: msg.description,
         image: msg.image,
         rarity: msg.rarity.to_string(),
         required_amount: vec![],
+        required_items,
         durability: msg.durability,
         token_uri: msg.token_uri,
     };
 
-    tool_template
-        .required_amount
-        .push(msg.required_gwood_amount);
-    tool_template
-        .required_amount
-        .push(msg.required_gfood_amount);
-    tool_template
-        .required_amount
-        .push(msg.required_ggold_amount);
-    tool_template
-        .required_amount
-        .push(msg.required_gstone_amount);
-
     let mut template_key = msg.tool_type;
     template_key.push_str(&msg.rarity);
     TOOL_TEMPLATE_MAP.save(deps.storage, template_key, &tool_template)?;
@@ -363,97 +962,195 @@ fn execute_add_tool_template(
         .add_attribute("tool template name", tool_template.name))
 }
 
-/// to withdraw tokens in exchange of game dev tokens
+/// to withdraw tokens in exchange of game dev tokens, the withdraw policy of the item limits
+/// the amount per rolling window and its fee stays in game for the stakeholders
 pub fn execute_withdraw(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     item_name: String,
     amount: Uint128,
-) -> StdResult<Response> {
-    let mut user_item_key = info.sender.to_string();
-
-    user_item_key.push_str(&item_name);
-
+) -> Result<Response, ContractError> {
     let mut user_item_amount = if let Some(user_item_amount) =
-        USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key.to_string())?
+        USER_ITEM_AMOUNT.may_load(deps.storage, (&info.sender, item_name.as_str()))?
     {
         user_item_amount
     } else {
         Uint128::zero()
     };
     if user_item_amount < amount {
-        return Err(StdError::generic_err("Insufficient funds"));
+        return Err(ContractError::InsufficientItem {
+            item: item_name,
+            have: user_item_amount,
+            need: amount,
+        });
     }
-    let token_addr = if let Some(token_addr) =
-        ITEM_TOKEN_MAPPING.may_load(deps.storage, item_name.to_string())?
-    {
-        token_addr
+    let item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+        item
     } else {
-        return Err(StdError::generic_err("Not found"));
+        return Err(ContractError::ItemTokenNotFound { item: item_name });
     };
+    let policy = WITHDRAW_POLICIES
+        .may_load(deps.storage, item_name.to_string())?
+        .unwrap_or_default();
+    let mut usage = policy.current_usage(
+        USER_WITHDRAWALS.may_load(deps.storage, (&info.sender, item_name.as_str()))?,
+        env.block.time.seconds(),
+    );
+    if let Some(remaining) = policy.remaining(&usage) {
+        if amount > remaining {
+            return Err(ContractError::WithdrawLimitReached {
+                item: item_name,
+                remaining,
+                frees_at: policy
+                    .frees_at(&usage)
+                    .unwrap_or_else(|| env.block.time.seconds()),
+            });
+        }
+        usage.record(env.block.time.seconds(), amount);
+        USER_WITHDRAWALS.save(deps.storage, (&info.sender, item_name.as_str()), &usage)?;
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let fee = amount.multiply_ratio(policy.fee_bps, BPS_DENOMINATOR);
+    distribute_amount(deps.storage, item_name.to_string(), fee, &config, &env)?;
     //transfering tokens to user
-    let response = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: token_addr,
-        msg: to_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: info.sender.to_string(),
-            amount,
-        })
-        .unwrap(),
-        funds: vec![],
-    });
+    let response = item_transfer_msg(&item, info.sender.as_str(), amount - fee)?;
     user_item_amount -= amount;
-    USER_ITEM_AMOUNT.save(deps.storage, user_item_key, &user_item_amount)?;
+    save_item_amount(deps.storage, &info.sender, &item_name, user_item_amount)?;
     Ok(Response::default()
         .add_message(response)
         .add_attribute("action", "withdraw")
         .add_attribute("item_name", item_name.to_string())
-        .add_attribute("amount", amount))
+        .add_attribute("amount", amount)
+        .add_attribute("fee", fee))
+}
+/// moves in-game item balances from the sender to the recipient, the transfer fee is taken
+/// from the amount sent and distributed between the stakeholders
+pub fn execute_send_item_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    items: Vec<ItemAmount>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let mut attributes = vec![];
+    for item in items {
+        let user_item_amount = USER_ITEM_AMOUNT
+            .may_load(deps.storage, (&info.sender, item.item_name.as_str()))?
+            .unwrap_or_default();
+        if user_item_amount < item.amount {
+            return Err(ContractError::InsufficientItem {
+                item: item.item_name,
+                have: user_item_amount,
+                need: item.amount,
+            });
+        }
+        save_item_amount(
+            deps.storage,
+            &info.sender,
+            &item.item_name,
+            user_item_amount - item.amount,
+        )?;
+        let fee = item
+            .amount
+            .multiply_ratio(config.transfer_fee_bps, BPS_DENOMINATOR);
+        distribute_amount(deps.storage, item.item_name.to_string(), fee, &config, &env)?;
+        add_amount_in_item_address(deps.storage, &recipient, &item.item_name, item.amount - fee)?;
+        attributes.push((item.item_name, (item.amount - fee).to_string()));
+    }
+    Ok(Response::new()
+        .add_attribute("action", "transfer item")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attributes(attributes))
 }
-///adding game dev token against tokns or vice versa e.g. gWood -> some address
+
+///registering game dev token against tokns or vice versa e.g. gWood -> some address,
+///a new item is added like AddItem, a registered item moves to the new token and keeps its
+///enabled flag. A token can only back one item
 pub fn execute_add_item_token(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
     item_name: String,
     item_token_addr: String,
-) -> StdResult<Response> {
+    decimals: Option<u8>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if info.sender != config.minter {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+    let item = if let Some(item) = ITEMS.may_load(deps.storage, item_name.to_string())? {
+        item
+    } else {
+        return execute_add_item(deps, info, item_name, item_token_addr, decimals, false);
+    };
+    // native items keep their bank denom
+    if item.native {
+        return Err(ContractError::NativeItem { item: item_name });
     }
+    if let Some(mapped_item) =
+        TOKEN_ITEM_MAPPING.may_load(deps.storage, item_token_addr.to_string())?
+    {
+        if mapped_item != item_name {
+            return Err(ContractError::DuplicateItem { item: mapped_item });
+        }
+    }
+    TOKEN_ITEM_MAPPING.remove(deps.storage, item.token_addr.to_string());
+    let item = ItemInfo {
+        token_addr: item_token_addr.to_string(),
+        decimals: decimals.unwrap_or(item.decimals),
+        enabled: item.enabled,
+        native: false,
+    };
     TOKEN_ITEM_MAPPING.save(deps.storage, item_token_addr.to_string(), &item_name)?;
-    ITEM_TOKEN_MAPPING.save(deps.storage, item_name.to_string(), &item_token_addr)?;
+    ITEMS.save(deps.storage, item_name.to_string(), &item)?;
     Ok(Response::default()
         .add_attribute("action", "add item token")
-        .add_attribute("item_name", item_name.to_string())
-        .add_attribute("item_address", item_token_addr.to_string()))
+        .add_event(
+            Event::new("item_token_updated")
+                .add_attribute("item_name", item_name)
+                .add_attribute("token_addr", item_token_addr)
+                .add_attribute("decimals", item.decimals.to_string()),
+        ))
 }
 
 ///adding reward token
 pub fn execute_add_reward_token(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
-    item_name: String,
-    tool_name: String,
-    mining_rate: u64,
-    mining_waiting_time: u64,
-) -> StdResult<Response> {
+    tool_type: String,
+    rarity: String,
+    reward_token: RewardToken,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     if info.sender != config.minter {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(schedule) = &reward_token.schedule {
+        if !schedule.is_valid() {
+            return Err(ContractError::InvalidRateSchedule {});
+        }
+    }
+    for output in reward_token.extra_outputs.iter() {
+        if output.chance_bps > BPS_DENOMINATOR {
+            return Err(ContractError::InvalidRewardOutput {
+                item: output.item_name.to_string(),
+            });
+        }
     }
 
-    let reward_token = RewardToken {
-        item_name,
-        mining_rate,
-        mining_waiting_time,
-    };
-
-    REWARD_TOKEN.save(deps.storage, tool_name, &reward_token)?;
-    Ok(Response::new().add_attribute("action", "distribution token added"))
+    REWARD_TOKEN.save(
+        deps.storage,
+        (tool_type.as_str(), rarity.as_str()),
+        &reward_token,
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "distribution token added")
+        .add_attribute("tool_type", tool_type)
+        .add_attribute("rarity", rarity))
 }
 
 /// adding tool type name
@@ -462,16 +1159,16 @@ pub fn execute_add_tool_type_names(
     _env: Env,
     info: MessageInfo,
     tool_type: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     if info.sender != config.minter {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
-    let mut tool_type_names = TOOL_TYPE_NAMES.may_load(deps.storage)?.unwrap();
+    let mut tool_type_names = TOOL_TYPE_NAMES.may_load(deps.storage)?.unwrap_or_default();
     for i in tool_type_names.iter() {
         if *i == tool_type {
-            return Err(StdError::generic_err("Already exist"));
+            return Err(ContractError::ToolTypeExists { tool_type });
         }
     }
 
@@ -480,17 +1177,77 @@ pub fn execute_add_tool_type_names(
     Ok(Response::new().add_attribute("action", "common name added"))
 }
 
+/// credits the native coins sent along to the in-game balances of the sender
+pub fn execute_deposit_native(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(ContractError::NoFundsSent {});
+    }
+    let mut attributes = vec![];
+    for coin in info.funds {
+        let item_name = TOKEN_ITEM_MAPPING.may_load(deps.storage, coin.denom.to_string())?;
+        let item = match &item_name {
+            Some(item_name) => ITEMS.may_load(deps.storage, item_name.to_string())?,
+            None => None,
+        };
+        let item_name = match (item_name, item) {
+            (Some(item_name), Some(item)) if item.native && item.enabled => item_name,
+            (Some(item_name), Some(item)) if item.native => {
+                return Err(ContractError::ItemDisabled { item: item_name })
+            }
+            _ => return Err(ContractError::UnknownDenom { denom: coin.denom }),
+        };
+        add_amount_in_item_address(deps.storage, &info.sender, &item_name, coin.amount)?;
+        attributes.push((item_name, coin.amount.to_string()));
+    }
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("sender", info.sender)
+        .add_attributes(attributes))
+}
+
+/// message paying `amount` of an item from the contract to `recipient`
+fn item_transfer_msg(item: &ItemInfo, recipient: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    if item.native {
+        return Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), &item.token_addr),
+        }));
+    }
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: item.token_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// message burning `amount` of an item held by the contract
+fn item_burn_msg(item: &ItemInfo, amount: Uint128) -> StdResult<CosmosMsg> {
+    if item.native {
+        return Ok(CosmosMsg::Bank(BankMsg::Burn {
+            amount: coins(amount.u128(), &item.token_addr),
+        }));
+    }
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: item.token_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }))
+}
+
 /// receiving cw20 tokens
 pub fn execute_receive_cw20(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     match from_binary(&msg.msg) {
         Ok(Cw20HookMsg::Deposit {}) => execute_deposit(deps, env, info, msg),
         Ok(Cw20HookMsg::AdminDeposit {}) => execute_admin_deposit(deps, env, info, msg),
-        Err(_err) => Err(StdError::generic_err("Already exist")),
+        Err(_err) => Err(ContractError::UnknownHookMsg {}),
     }
 }
 
@@ -500,25 +1257,35 @@ pub fn execute_deposit(
     _env: Env,
     info: MessageInfo,
     msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let item_name = if let Some(item_name) =
         TOKEN_ITEM_MAPPING.may_load(deps.storage, info.sender.to_string())?
     {
         item_name
     } else {
-        return Err(StdError::generic_err("No token item found"));
+        return Err(ContractError::UnknownItemToken {
+            token_addr: info.sender.to_string(),
+        });
     };
-    let mut user_item_key = msg.sender.to_string();
-    user_item_key.push_str(&item_name);
+    let item = ITEMS.load(deps.storage, item_name.to_string())?;
+    if item.native {
+        return Err(ContractError::UnknownItemToken {
+            token_addr: info.sender.to_string(),
+        });
+    }
+    if !item.enabled {
+        return Err(ContractError::ItemDisabled { item: item_name });
+    }
+    let sender = deps.api.addr_validate(&msg.sender)?;
     let mut user_item_amount = if let Some(user_item_amount) =
-        USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key.to_string())?
+        USER_ITEM_AMOUNT.may_load(deps.storage, (&sender, item_name.as_str()))?
     {
         user_item_amount
     } else {
         Uint128::zero()
     };
     user_item_amount += msg.amount;
-    USER_ITEM_AMOUNT.save(deps.storage, user_item_key, &user_item_amount)?;
+    save_item_amount(deps.storage, &sender, &item_name, user_item_amount)?;
     Ok(Response::new()
         .add_attribute("action", "deposit")
         .add_attribute("sender", msg.sender)
@@ -531,29 +1298,34 @@ pub fn execute_admin_deposit(
     env: Env,
     info: MessageInfo,
     msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if config.minter != msg.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
     let item_name = if let Some(item_name) =
         TOKEN_ITEM_MAPPING.may_load(deps.storage, info.sender.to_string())?
     {
         item_name
     } else {
-        return Err(StdError::generic_err("Not found"));
+        return Err(ContractError::UnknownItemToken {
+            token_addr: info.sender.to_string(),
+        });
     };
-    let mut contract_item_key = env.contract.address.to_string();
-    contract_item_key.push_str(&item_name);
     let mut contract_item_amount = if let Some(contract_item_amount) =
-        USER_ITEM_AMOUNT.may_load(deps.storage, contract_item_key.to_string())?
+        USER_ITEM_AMOUNT.may_load(deps.storage, (&env.contract.address, item_name.as_str()))?
     {
         contract_item_amount
     } else {
         Uint128::zero()
     };
     contract_item_amount += msg.amount;
-    USER_ITEM_AMOUNT.save(deps.storage, contract_item_key, &contract_item_amount)?;
+    save_item_amount(
+        deps.storage,
+        &env.contract.address,
+        &item_name,
+        contract_item_amount,
+    )?;
     Ok(Response::new()
         .add_attribute("action", "admin deposit")
         .add_attribute("sender", msg.sender)
@@ -566,35 +1338,49 @@ pub fn execute_refill_energy(
     env: Env,
     info: MessageInfo,
     amount: u64,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    let energy_config = ENERGY_CONFIG.load(deps.storage)?;
+    let refill_item = energy_config.refill_item.as_str();
+
+    let (mut user_energy_level, energy_accounted_at) = load_energy(
+        deps.storage,
+        &energy_config,
+        info.sender.as_str(),
+        env.block.time.seconds(),
+    )?;
 
-    let mut user_energy_level = if let Some(user_energy_level) =
-        USER_ENERGY_LEVEL.may_load(deps.storage, info.sender.to_string())?
+    let mut user_item_amount = if let Some(user_item_amount) =
+        USER_ITEM_AMOUNT.may_load(deps.storage, (&info.sender, refill_item))?
     {
-        user_energy_level
+        user_item_amount
     } else {
         Uint128::zero()
     };
-
-    let mut user_item_key = info.sender.to_string();
-    user_item_key.push_str("gFood");
-    let mut user_item_amount =
-        if let Some(user_item_amount) = USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key)? {
-            user_item_amount
-        } else {
-            Uint128::zero()
-        };
     let amount = Uint128::from(amount);
     if user_item_amount < amount {
-        return Err(StdError::generic_err("Insufficient funds"));
+        return Err(ContractError::InsufficientItem {
+            item: refill_item.to_string(),
+            have: user_item_amount,
+            need: amount,
+        });
+    }
+    user_energy_level += amount.multiply_ratio(energy_config.refill_ratio, Uint128::from(1u128));
+    if let Some(max_energy) = energy_config.max_energy {
+        if user_energy_level > max_energy {
+            return Err(ContractError::EnergyCapExceeded { max: max_energy });
+        }
     }
-    user_energy_level += amount.multiply_ratio(Uint128::from(3u128), Uint128::from(1u128));
 
-    USER_ENERGY_LEVEL.save(deps.storage, info.sender.to_string(), &user_energy_level)?;
+    save_energy(
+        deps.storage,
+        info.sender.as_str(),
+        user_energy_level,
+        energy_accounted_at,
+    )?;
     user_item_amount -= amount;
-    USER_ITEM_AMOUNT.save(deps.storage, info.sender.to_string(), &user_item_amount)?;
-    distribute_amount(deps.storage, "gFood".to_string(), amount, &config, &env);
+    save_item_amount(deps.storage, &info.sender, refill_item, user_item_amount)?;
+    distribute_amount(deps.storage, refill_item.to_string(), amount, &config, &env)?;
     Ok(Response::new()
         .add_attribute("action", "refill energy")
         .add_attribute("sender", info.sender)
@@ -608,7 +1394,7 @@ pub fn execute_transfer_nft(
     info: MessageInfo,
     recipient: String,
     token_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     _transfer_nft(deps, &env, &info, &recipient, &token_id)?;
 
     Ok(Response::new()
@@ -626,7 +1412,7 @@ pub fn execute_send_nft(
     contract: String,
     token_id: String,
     msg: Binary,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // Transfer token
     _transfer_nft(deps, &env, &info, &contract, &token_id)?;
 
@@ -651,39 +1437,39 @@ pub fn execute_receive_cw721(
     env: Env,
     info: MessageInfo,
     msg: Cw721ReceiveMsg,
-) -> StdResult<Response> {
-    println!("contract address {} ", env.contract.address);
+) -> Result<Response, ContractError> {
     if env.contract.address != info.sender {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
     match from_binary(&msg.msg) {
-        Ok(Cw721HookMsg::Stake {}) => execute_stake(deps, env, msg),
+        Ok(Cw721HookMsg::Stake {}) => execute_stake(deps, env, msg, vec![]),
+        Ok(Cw721HookMsg::StakeMany { token_ids }) => execute_stake(deps, env, msg, token_ids),
         Ok(Cw721HookMsg::OpenPack {}) => execute_open_pack(deps, env, msg),
         Ok(Cw721HookMsg::StakeRepairKit {}) => execute_stake_repair_kit(deps, info, env, msg),
-        Err(_err) => Err(StdError::generic_err("no method found")),
+        Err(_err) => Err(ContractError::UnknownHookMsg {}),
     }
 }
 
 /// staking nft for earning reward
-pub fn execute_stake(deps: DepsMut, env: Env, msg: Cw721ReceiveMsg) -> StdResult<Response> {
+pub fn execute_stake(
+    mut deps: DepsMut,
+    env: Env,
+    msg: Cw721ReceiveMsg,
+    extra_token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let mut token = tokens().load(deps.storage, &msg.token_id)?;
-
-    // check that only pack can be opened, not any ohter nft from our contract
-    if token.is_pack_token {
-        return Err(StdError::generic_err(
-            "Not eligible because provided token is a pack token",
-        ));
-    }
-    // if user is staking first time than the user will get 200 energy
+    let staker = deps.api.addr_validate(&msg.sender)?;
+    // if user is staking first time than the user will get the starter energy
     if USER_ENERGY_LEVEL
         .may_load(deps.storage, msg.sender.to_string())?
         .is_none()
     {
-        USER_ENERGY_LEVEL.save(
+        let energy_config = ENERGY_CONFIG.load(deps.storage)?;
+        save_energy(
             deps.storage,
-            msg.sender.to_string(),
-            &Uint128::from(200u128),
+            &msg.sender,
+            energy_config.cap(energy_config.starter_energy),
+            env.block.time.seconds(),
         )?;
     }
 
@@ -695,18 +1481,89 @@ pub fn execute_stake(deps: DepsMut, env: Env, msg: Cw721ReceiveMsg) -> StdResult
         HashSet::<String>::new()
     };
 
-    if stake_info.len() as u64 > config.stake_limit {
-        return Err(StdError::generic_err("Limit reached"));
+    stake_token(
+        deps.storage,
+        &env,
+        &config,
+        &staker,
+        &mut stake_info,
+        &msg.token_id,
+    )?;
+    let mut response = Response::new()
+        .add_attribute("action", "stake")
+        .add_attribute("sender", msg.sender.to_string())
+        .add_attribute("token_id", msg.token_id);
+
+    // pull the remaining tokens into custody, the contract has to be approved by the staker
+    let contract_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    for token_id in extra_token_ids {
+        let token = tokens().load(deps.storage, &token_id)?;
+        if token.owner != staker {
+            return Err(ContractError::Unauthorized {});
+        }
+        _transfer_nft(
+            deps.branch(),
+            &env,
+            &contract_info,
+            env.contract.address.as_str(),
+            &token_id,
+        )?;
+        stake_token(
+            deps.storage,
+            &env,
+            &config,
+            &staker,
+            &mut stake_info,
+            &token_id,
+        )?;
+        response = response.add_attribute("token_id", token_id);
+    }
+    USER_STAKED_INFO.save(deps.storage, msg.sender.to_string(), &stake_info)?;
+    Ok(response)
+}
+
+/// marks a token held by the contract as staked by `staker`
+fn stake_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    staker: &Addr,
+    stake_info: &mut HashSet<String>,
+    token_id: &str,
+) -> Result<(), ContractError> {
+    let mut token = tokens().load(storage, token_id)?;
+
+    // check that only pack can be opened, not any ohter nft from our contract
+    if token.is_pack_token {
+        return Err(ContractError::IsPackToken {
+            token_id: token_id.to_string(),
+        });
+    }
+    // the hook is only valid once the token is held by the contract
+    if token.owner != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if token.staked_by.is_some() {
+        return Err(ContractError::TokenStaked {
+            token_id: token_id.to_string(),
+        });
+    }
+    if stake_info.len() as u64 >= config.stake_limit {
+        return Err(ContractError::StakeLimitReached {
+            limit: config.stake_limit,
+        });
     }
 
     token.reward_start_time = env.block.time.seconds();
-    stake_info.insert(msg.token_id.to_string());
-    USER_STAKED_INFO.save(deps.storage, msg.sender.to_string(), &stake_info)?;
-    tokens().save(deps.storage, &msg.token_id, &token)?;
-    Ok(Response::new()
-        .add_attribute("action", "stake")
-        .add_attribute("sender", msg.sender.to_string())
-        .add_attribute("token_id", msg.token_id))
+    token.staked_by = Some(staker.clone());
+    token.staked_at = Some(env.block.time.seconds());
+    stake_info.insert(token_id.to_string());
+    tokens().save(storage, token_id, &token)?;
+    count_staked_tool(storage, &token, true)?;
+    Ok(())
 }
 
 /// unstaking nft
@@ -715,54 +1572,142 @@ pub fn execute_unstake(
     env: Env,
     info: MessageInfo,
     token_id: String,
-) -> StdResult<Response> {
-    let mut responses: Vec<CosmosMsg> = vec![];
-    let mut stake_ids_set = if let Some(stake_ids_set) =
-        USER_STAKED_INFO.may_load(deps.storage, info.sender.to_string())?
-    {
-        stake_ids_set
-    } else {
-        return Err(StdError::generic_err("No staked asset found"));
-    };
-
-    if !stake_ids_set.contains(&token_id.to_string()) {
-        return Err(StdError::generic_err("No token id found"));
-    }
-    let token_info = tokens().load(deps.storage, &token_id)?;
-    let reward_info = REWARD_TOKEN.load(deps.storage, token_info.name.to_string())?;
-    if token_info.reward_start_time + reward_info.mining_waiting_time > env.block.time.seconds() {
-        return Err(StdError::generic_err("Time not reached yet"));
-    }
-    // transfer it back to user
-    responses.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: env.contract.address.into_string(),
-        msg: to_binary(&ExecuteMsg::TransferNft {
-            recipient: info.sender.to_string(),
-            token_id: token_id.to_string(),
-        })?,
-        funds: vec![],
-    }));
-    stake_ids_set.remove(&token_id);
+) -> Result<Response, ContractError> {
+    let mut stake_ids_set = USER_STAKED_INFO
+        .may_load(deps.storage, info.sender.to_string())?
+        .unwrap_or_default();
+    unstake_token(
+        deps.storage,
+        &env,
+        &info.sender,
+        &mut stake_ids_set,
+        &token_id,
+    )?;
     USER_STAKED_INFO.save(deps.storage, info.sender.to_string(), &stake_ids_set)?;
     Ok(Response::new()
-        .add_messages(responses)
         .add_attribute("action", "Unstake")
         .add_attribute("sender", info.sender)
         .add_attribute("token_id", token_id))
 }
 
+/// unstaking several nfts, tokens that are not staked by the sender or still mining are reported
+/// instead of failing the batch, any other error fails it
+pub fn execute_unstake_many(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut stake_ids_set = USER_STAKED_INFO
+        .may_load(deps.storage, info.sender.to_string())?
+        .unwrap_or_default();
+    let mut unstaked = 0u64;
+    let mut results = vec![];
+    for token_id in token_ids {
+        let result = match unstake_token(
+            deps.storage,
+            &env,
+            &info.sender,
+            &mut stake_ids_set,
+            &token_id,
+        ) {
+            Ok(()) => {
+                unstaked += 1;
+                "unstaked".to_string()
+            }
+            Err(
+                err @ (ContractError::TokenNotStaked { .. } | ContractError::MiningNotReady { .. }),
+            ) => err.to_string(),
+            Err(err) => return Err(err),
+        };
+        results.push((format!("token_{}", token_id), result));
+    }
+    USER_STAKED_INFO.save(deps.storage, info.sender.to_string(), &stake_ids_set)?;
+    Ok(Response::new()
+        .add_attribute("action", "unstake many")
+        .add_attribute("sender", info.sender)
+        .add_attribute("unstaked", unstaked.to_string())
+        .add_attributes(results))
+}
+
+/// hands a staked token back to the staker and clears the staking state in one write
+fn unstake_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    stake_ids_set: &mut HashSet<String>,
+    token_id: &str,
+) -> Result<(), ContractError> {
+    if !stake_ids_set.contains(token_id) {
+        return Err(ContractError::TokenNotStaked {
+            token_id: token_id.to_string(),
+        });
+    }
+    let mut token_info = tokens().load(storage, token_id)?;
+    if token_info.staked_by.as_ref() != Some(sender) {
+        return Err(ContractError::TokenNotStaked {
+            token_id: token_id.to_string(),
+        });
+    }
+    // a tool without reward has nothing to wait for
+    let mining_waiting_time = REWARD_TOKEN
+        .may_load(
+            storage,
+            (token_info.tool_type.as_str(), token_info.rarity.as_str()),
+        )?
+        .map(|reward_info| reward_info.mining_waiting_time)
+        .unwrap_or_default();
+    let multiplier = rarity_multiplier(storage, &token_info.rarity)?;
+    let ready_at = token_info.reward_start_time + multiplier.waiting_time(mining_waiting_time);
+    if ready_at > env.block.time.seconds() {
+        return Err(ContractError::MiningNotReady {
+            token_id: token_id.to_string(),
+            ready_at,
+        });
+    }
+    token_info.owner = sender.clone();
+    token_info.approvals = vec![];
+    token_info.staked_by = None;
+    token_info.staked_at = None;
+    tokens().save(storage, token_id, &token_info)?;
+    stake_ids_set.remove(token_id);
+    count_staked_tool(storage, &token_info, false)?;
+    Ok(())
+}
+
+/// keeps the number of staked tools per tool type and rarity used to estimate emission
+fn count_staked_tool(storage: &mut dyn Storage, token: &TokenInfo, staked: bool) -> StdResult<()> {
+    let key = (token.tool_type.as_str(), token.rarity.as_str());
+    let staked_count = STAKED_TOOL_COUNT
+        .may_load(storage, key)?
+        .unwrap_or_default();
+    let staked_count = if staked {
+        staked_count + 1
+    } else {
+        staked_count.saturating_sub(1)
+    };
+    STAKED_TOOL_COUNT.save(storage, key, &staked_count)
+}
+
 ///let user open pack
-pub fn execute_open_pack(deps: DepsMut, env: Env, msg: Cw721ReceiveMsg) -> StdResult<Response> {
+pub fn execute_open_pack(
+    deps: DepsMut,
+    env: Env,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
     let token = tokens().load(deps.storage, &msg.token_id)?;
     let mut responses: Vec<CosmosMsg> = vec![];
 
     // check that only pack can be opened, not any other nft from our contract
     if !token.is_pack_token {
-        return Err(StdError::generic_err(
-            "Not eligible as token is not a pack token",
-        ));
+        return Err(ContractError::NotPackToken {
+            token_id: msg.token_id,
+        });
+    }
+    let tool_types: Vec<String> = TOOL_TYPE_NAMES.may_load(deps.storage)?.unwrap_or_default();
+    if tool_types.is_empty() {
+        return Err(ContractError::NoToolAvailable {});
     }
-    let tool_types: Vec<String> = TOOL_TYPE_NAMES.may_load(deps.storage)?.unwrap();
     let contract_addr = env.clone().contract.address.into_string();
     let mut result: u64 = 0u64;
     let user_addr_bytes = msg.clone().sender.into_bytes();
@@ -771,7 +1716,11 @@ pub fn execute_open_pack(deps: DepsMut, env: Env, msg: Cw721ReceiveMsg) -> StdRe
     }
     let mut transfered;
     let mut number_iterated: HashSet<u64> = HashSet::<u64>::new();
-    let mut number_to_add = result + msg.clone().token_id.parse::<u64>().unwrap();
+    let mut number_to_add = result
+        + msg
+            .token_id
+            .parse::<u64>()
+            .map_err(|e| StdError::parse_err("u64", e))?;
     let mut time_in_epoch_seconds = env.block.time.nanos();
 
     // transfering pre minted tool
@@ -794,7 +1743,7 @@ pub fn execute_open_pack(deps: DepsMut, env: Env, msg: Cw721ReceiveMsg) -> StdRe
     );
 
     // if preminted tool set is no more available
-    if !transfered.unwrap() {
+    if !transfered? {
         number = 4;
     }
 
@@ -802,17 +1751,17 @@ pub fn execute_open_pack(deps: DepsMut, env: Env, msg: Cw721ReceiveMsg) -> StdRe
         transfered = transfer_pack_nfts(
             deps.storage,
             time_in_epoch_seconds,
-            tool_types.get(random_number as usize).unwrap().to_string(),
+            tool_types[random_number as usize].to_string(),
             &msg,
             &mut responses,
             contract_addr.clone(),
             number_to_add,
         );
         if failure_count_of_transfer_pack == number {
-            return Err(StdError::generic_err("No Tool available to distribute"));
+            return Err(ContractError::NoToolAvailable {});
         }
         //if random index hit not available tool set
-        if !transfered.unwrap() {
+        if !transfered? {
             random_number = (random_number + 1) % tool_types.len() as u64;
             if !number_iterated.contains(&random_number) {
                 number_iterated.insert(random_number);
@@ -850,20 +1799,31 @@ pub fn execute_stake_repair_kit(
     _info: MessageInfo,
     env: Env,
     msg: Cw721ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut token = if let Some(token) = tokens().may_load(deps.storage, &msg.token_id)? {
         token
     } else {
-        return Err(StdError::generic_err("No Token Found"));
+        return Err(ContractError::TokenNotFound {
+            token_id: msg.token_id,
+        });
     };
-    let mut user_repair_kit_key = msg.sender.to_string();
-    user_repair_kit_key.push_str(REPAIR_KIT_KEYWORD);
-    user_repair_kit_key.push_str(token.tool_type.to_string().as_str());
-    if USER_REPAIR_KITS.has(deps.storage, user_repair_kit_key.to_string()) {
-        return Err(StdError::generic_err("Repair kit is already deployed"));
+    if token.staked_by.is_some() {
+        return Err(ContractError::TokenStaked {
+            token_id: msg.token_id,
+        });
     }
-    USER_REPAIR_KITS.save(deps.storage, user_repair_kit_key, &msg.token_id)?;
+    let sender = deps.api.addr_validate(&msg.sender)?;
+    if USER_REPAIR_KITS.has(deps.storage, (&sender, token.tool_type.as_str())) {
+        return Err(ContractError::RepairKitAlreadyDeployed {
+            tool_type: token.tool_type,
+        });
+    }
+    USER_REPAIR_KITS.save(
+        deps.storage,
+        (&sender, token.tool_type.as_str()),
+        &msg.token_id,
+    )?;
     token.repair_kit_available_time = env.block.time.seconds() + config.repair_kit_waiting_time;
     tokens().save(deps.storage, &msg.token_id, &token)?;
     Ok(Response::new()
@@ -877,35 +1837,40 @@ pub fn execute_unstake_repair_tool(
     info: MessageInfo,
     env: Env,
     repair_kit_token_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let token = if let Some(token) = tokens().may_load(deps.storage, &repair_kit_token_id)? {
         token
     } else {
-        return Err(StdError::generic_err("no token available"));
+        return Err(ContractError::TokenNotFound {
+            token_id: repair_kit_token_id,
+        });
     };
 
-    let mut user_repair_kit_key = info.sender.to_string();
-    user_repair_kit_key.push_str(REPAIR_KIT_KEYWORD);
-    user_repair_kit_key.push_str(token.tool_type.to_string().as_str());
-
     let user_repair_kit_id = if let Some(user_repair_kit_id) =
-        USER_REPAIR_KITS.may_load(deps.storage, user_repair_kit_key.to_string())?
+        USER_REPAIR_KITS.may_load(deps.storage, (&info.sender, token.tool_type.as_str()))?
     {
         user_repair_kit_id
     } else {
-        return Err(StdError::generic_err("User do not stake any repair kit"));
+        return Err(ContractError::RepairKitNotDeployed {
+            tool_type: token.tool_type,
+        });
     };
     let user_repair_kit_token = if let Some(user_repair_kit_token) =
         tokens().may_load(deps.storage, &user_repair_kit_id)?
     {
         user_repair_kit_token
     } else {
-        return Err(StdError::generic_err("no user repair kit token available"));
+        return Err(ContractError::TokenNotFound {
+            token_id: user_repair_kit_id,
+        });
     };
     if user_repair_kit_token.repair_kit_available_time < env.block.time.seconds() {
-        USER_REPAIR_KITS.remove(deps.storage, user_repair_kit_key);
+        USER_REPAIR_KITS.remove(deps.storage, (&info.sender, token.tool_type.as_str()));
     } else {
-        return Err(StdError::generic_err("Time not reached yet"));
+        return Err(ContractError::RepairKitNotReady {
+            token_id: user_repair_kit_id,
+            ready_at: user_repair_kit_token.repair_kit_available_time,
+        });
     }
     let response = CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: env.contract.address.into_string(),
@@ -928,77 +1893,93 @@ pub fn execute_use_repair_tool(
     info: MessageInfo,
     env: Env,
     token_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut token = if let Some(token) = tokens().may_load(deps.storage, &token_id)? {
         token
     } else {
-        return Err(StdError::generic_err("no token available"));
+        return Err(ContractError::TokenNotFound { token_id });
     };
     let mut template_key = token.tool_type.to_string();
     template_key.push_str(token.rarity.to_string().as_str());
     let tool_template = TOOL_TEMPLATE_MAP.load(deps.storage, template_key)?;
-    let mut user_repair_kit_key = info.sender.to_string();
-    user_repair_kit_key.push_str(REPAIR_KIT_KEYWORD);
-    user_repair_kit_key.push_str(token.tool_type.to_string().as_str());
     let repairing_fee = if let Some(repairing_fee) =
         REPAIRING_FEE.may_load(deps.storage, token.tool_type.to_string())?
     {
         repairing_fee
     } else {
-        return Err(StdError::generic_err("Repairing Fee is not set"));
+        return Err(ContractError::RepairingFeeNotSet {
+            key: token.tool_type,
+        });
     };
     let user_repair_kit_id = if let Some(user_repair_kit_id) =
-        USER_REPAIR_KITS.may_load(deps.storage, user_repair_kit_key)?
+        USER_REPAIR_KITS.may_load(deps.storage, (&info.sender, token.tool_type.as_str()))?
     {
         user_repair_kit_id
     } else {
-        return Err(StdError::generic_err(
-            "User do not deploy any tool's repair kit",
-        ));
+        return Err(ContractError::RepairKitNotDeployed {
+            tool_type: token.tool_type,
+        });
+    };
+    let reward_item = if let Some(reward_item) = REWARD_TOKEN.may_load(
+        deps.storage,
+        (token.tool_type.as_str(), token.rarity.as_str()),
+    )? {
+        reward_item
+    } else {
+        return Err(ContractError::RewardTokenNotFound {
+            tool_type: token.tool_type,
+            rarity: token.rarity,
+        });
+    };
+    let mut user_item_amount = if let Some(user_item_amount) =
+        USER_ITEM_AMOUNT.may_load(deps.storage, (&info.sender, reward_item.item_name.as_str()))?
+    {
+        user_item_amount
+    } else {
+        Uint128::zero()
     };
-    let reward_item =
-        if let Some(reward_item) = REWARD_TOKEN.may_load(deps.storage, token.name.to_string())? {
-            reward_item
-        } else {
-            return Err(StdError::generic_err("No item found against tool"));
-        };
-    let mut user_item_key = info.sender.to_string();
-    user_item_key.push_str(reward_item.item_name.as_str());
-    let mut user_item_amount =
-        if let Some(user_item_amount) = USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key)? {
-            user_item_amount
-        } else {
-            return Err(StdError::generic_err("No item available in user account"));
-        };
     if user_item_amount < repairing_fee {
-        return Err(StdError::generic_err("Insufficient items"));
+        return Err(ContractError::InsufficientItem {
+            item: reward_item.item_name,
+            have: user_item_amount,
+            need: repairing_fee,
+        });
     }
     user_item_amount -= repairing_fee;
+    save_item_amount(
+        deps.storage,
+        &info.sender,
+        &reward_item.item_name,
+        user_item_amount,
+    )?;
     distribute_amount(
         deps.storage,
         reward_item.item_name.to_string(),
         repairing_fee,
         &config,
         &env,
-    );
+    )?;
     let mut user_repair_kit_token = if let Some(user_repair_kit_token) =
         tokens().may_load(deps.storage, &user_repair_kit_id)?
     {
         user_repair_kit_token
     } else {
-        return Err(StdError::generic_err("no user repair kit token available"));
+        return Err(ContractError::TokenNotFound {
+            token_id: user_repair_kit_id,
+        });
     };
     if token.durability == tool_template.durability {
-        return Err(StdError::generic_err(
-            "tool is perfectly fine no need to repair",
-        ));
+        return Err(ContractError::ToolNotDamaged { token_id });
     }
     if user_repair_kit_token.repair_kit_available_time < env.block.time.seconds() {
         token.durability = tool_template.durability;
         user_repair_kit_token.repair_kit_available_time = env.block.time.seconds();
     } else {
-        return Err(StdError::generic_err("Time not reached yet"));
+        return Err(ContractError::RepairKitNotReady {
+            token_id: user_repair_kit_id,
+            ready_at: user_repair_kit_token.repair_kit_available_time,
+        });
     }
 
     tokens().save(deps.storage, &token_id, &token)?;
@@ -1015,36 +1996,32 @@ pub fn transfer_pack_nfts(
     responses: &mut Vec<CosmosMsg>,
     contract_addr: String,
     number_to_add: u64,
-) -> StdResult<bool> {
+) -> Result<bool, ContractError> {
     time_in_epoch_seconds += number_to_add;
 
     let mut token_ids =
         if let Some(token_ids) = TOOL_SET_MAP.may_load(store, tool_type.to_string())? {
             token_ids
         } else {
-            let mut message = " tool type: ".to_string();
-            message.push_str(&tool_type);
-            message.push_str(" error in token ids");
-            return Err(StdError::generic_err(message));
+            return Err(ContractError::ToolSetNotFound { tool_type });
         };
 
+    if token_ids.is_empty() {
+        return Ok(false);
+    }
     let random_number = generate_random_number(
         time_in_epoch_seconds + token_ids.len() as u64,
         token_ids.len() as u64,
     );
-    if token_ids.is_empty() {
-        return Ok(false);
-    }
     let token_id = token_ids.swap_remove(random_number as usize);
-    TOOL_SET_MAP.save(store, tool_type, &token_ids).unwrap();
+    TOOL_SET_MAP.save(store, tool_type, &token_ids)?;
 
     responses.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr,
         msg: to_binary(&ExecuteMsg::TransferNft {
             recipient: msg.sender.to_string(),
             token_id,
-        })
-        .unwrap(),
+        })?,
         funds: vec![],
     }));
 
@@ -1058,8 +2035,14 @@ pub fn _transfer_nft(
     info: &MessageInfo,
     recipient: &str,
     token_id: &str,
-) -> StdResult<TokenInfo> {
+) -> Result<TokenInfo, ContractError> {
     let mut token = tokens().load(deps.storage, token_id)?;
+    // staked tokens only leave the contract through unstake
+    if token.staked_by.is_some() {
+        return Err(ContractError::TokenStaked {
+            token_id: token_id.to_string(),
+        });
+    }
     // ensure we have permissions
     check_can_send(deps.as_ref(), env, info, &token)?;
     // set owner and remove existing approvals
@@ -1076,7 +2059,7 @@ pub fn execute_approve(
     spender: String,
     token_id: String,
     expires: Option<Expiration>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     _update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
 
     Ok(Response::new()
@@ -1092,85 +2075,231 @@ pub fn execute_claim_reward(
     env: Env,
     info: MessageInfo,
     token_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let mut token_info = if let Some(token_info) = tokens().may_load(deps.storage, &token_id)? {
-        token_info
-    } else {
-        return Err(StdError::generic_err("No token found"));
-    };
+    let energy_config = ENERGY_CONFIG.load(deps.storage)?;
+    let (mut user_energy_level, energy_accounted_at) = load_energy(
+        deps.storage,
+        &energy_config,
+        info.sender.as_str(),
+        env.block.time.seconds(),
+    )?;
 
-    let mut user_energy_level = if let Some(user_energy_level) =
-        USER_ENERGY_LEVEL.may_load(deps.storage, info.sender.to_string())?
-    {
-        user_energy_level
+    let claimed = claim_token_reward(
+        deps.storage,
+        &env,
+        &config,
+        &energy_config,
+        &info.sender,
+        &mut user_energy_level,
+        &token_id,
+    )?;
+    save_energy(
+        deps.storage,
+        info.sender.as_str(),
+        user_energy_level,
+        energy_accounted_at,
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "claim reward")
+        .add_attribute("sender", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attributes(claimed))
+}
+
+/// claiming reward of several staked tools, all staked tools when `token_ids` is None. Tools
+/// that are not staked by the sender, still mining, lack energy, need repair or whose reward
+/// budget or pool is exhausted are reported instead of failing the batch, any other error fails it
+pub fn execute_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_ids: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let energy_config = ENERGY_CONFIG.load(deps.storage)?;
+    let token_ids = if let Some(token_ids) = token_ids {
+        token_ids
     } else {
-        return Err(StdError::generic_err("No energy"));
+        let mut token_ids: Vec<String> = USER_STAKED_INFO
+            .may_load(deps.storage, info.sender.to_string())?
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        token_ids.sort();
+        token_ids
     };
-
-    if user_energy_level < Uint128::from(3u128) {
-        return Err(StdError::generic_err("Not enough energy"));
+    let (mut user_energy_level, energy_accounted_at) = load_energy(
+        deps.storage,
+        &energy_config,
+        info.sender.as_str(),
+        env.block.time.seconds(),
+    )?;
+
+    let mut claimed = 0u64;
+    let mut results = vec![];
+    for token_id in token_ids {
+        let result = match claim_token_reward(
+            deps.storage,
+            &env,
+            &config,
+            &energy_config,
+            &info.sender,
+            &mut user_energy_level,
+            &token_id,
+        ) {
+            Ok(outputs) => {
+                claimed += 1;
+                let outputs = outputs
+                    .iter()
+                    .map(|(item_name, amount)| format!("{} {}", amount, item_name))
+                    .collect::<Vec<String>>();
+                format!("claimed {}", outputs.join(", "))
+            }
+            Err(
+                err @ (ContractError::TokenNotStaked { .. }
+                | ContractError::MiningNotReady { .. }
+                | ContractError::InsufficientEnergy { .. }
+                | ContractError::NeedsRepair { .. }
+                | ContractError::EmissionCapReached { .. }
+                | ContractError::InsufficientPool { .. }),
+            ) => err.to_string(),
+            Err(err) => return Err(err),
+        };
+        results.push((format!("token_{}", token_id), result));
     }
+    save_energy(
+        deps.storage,
+        info.sender.as_str(),
+        user_energy_level,
+        energy_accounted_at,
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "claim rewards")
+        .add_attribute("sender", info.sender)
+        .add_attribute("claimed", claimed.to_string())
+        .add_attributes(results))
+}
 
-    user_energy_level -= Uint128::from(3u128);
-
-    let stake_info = if let Some(stake_info) =
-        USER_STAKED_INFO.may_load(deps.storage, info.sender.to_string())?
-    {
-        stake_info
+/// moves the mined amounts of one staked tool from the contract pools to the staker and
+/// deducts the claim energy, every check runs before the first write so a failed claim
+/// leaves no trace. Only the main output can fail the claim, extra outputs pay what their
+/// pool and emission budget allow
+fn claim_token_reward(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    energy_config: &EnergyConfig,
+    sender: &Addr,
+    user_energy_level: &mut Uint128,
+    token_id: &str,
+) -> Result<Vec<(String, Uint128)>, ContractError> {
+    let mut token_info = if let Some(token_info) = tokens().may_load(storage, token_id)? {
+        token_info
     } else {
-        return Err(StdError::generic_err("No staked asset found"));
+        return Err(ContractError::TokenNotFound {
+            token_id: token_id.to_string(),
+        });
     };
-
-    if !stake_info.contains(&token_id) {
-        return Err(StdError::generic_err("No token id found"));
+    if token_info.staked_by.as_ref() != Some(sender) {
+        return Err(ContractError::TokenNotStaked {
+            token_id: token_id.to_string(),
+        });
     }
-    let reward_token = if let Some(reward_token) =
-        REWARD_TOKEN.may_load(deps.storage, token_info.name.to_string())?
-    {
+    let energy_cost = energy_config.claim_cost(&token_info.tool_type, &token_info.rarity);
+    if *user_energy_level < energy_cost {
+        return Err(ContractError::InsufficientEnergy {
+            have: *user_energy_level,
+            need: energy_cost,
+        });
+    }
+    let reward_token = if let Some(reward_token) = REWARD_TOKEN.may_load(
+        storage,
+        (token_info.tool_type.as_str(), token_info.rarity.as_str()),
+    )? {
         reward_token
     } else {
-        return Err(StdError::generic_err("No reward token found"));
+        return Err(ContractError::RewardTokenNotFound {
+            tool_type: token_info.tool_type,
+            rarity: token_info.rarity,
+        });
     };
 
-    if token_info.reward_start_time + reward_token.mining_waiting_time < env.block.time.seconds() {
-        let mut user_item_key = info.sender.to_string();
-        user_item_key.push_str(&reward_token.item_name);
-        let mut user_item_amount = if let Some(user_item_amount) =
-            USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key.to_string())?
-        {
-            user_item_amount
-        } else {
-            Uint128::zero()
-        };
-        if token_info.durability < 1 {
-            return Err(StdError::generic_err(
-                "Kindly repair the tool first to claim reward",
-            ));
-        }
-        if config.durability_start_time < env.block.time.seconds() {
-            token_info.durability -= 1;
-        }
-
-        user_item_amount += Uint128::from(reward_token.mining_rate);
-        USER_ITEM_AMOUNT.save(deps.storage, user_item_key.to_string(), &user_item_amount)?;
-        let mut contract_item_key = env.contract.address.to_string();
-        contract_item_key.push_str(&reward_token.item_name);
-        let mut contract_item_amount = USER_ITEM_AMOUNT
-            .may_load(deps.storage, contract_item_key.to_string())?
-            .unwrap();
-        contract_item_amount -= Uint128::from(reward_token.mining_rate);
-        USER_ITEM_AMOUNT.save(deps.storage, contract_item_key, &contract_item_amount)?;
-        token_info.reward_start_time = env.block.time.seconds();
-        tokens().save(deps.storage, &token_id, &token_info)?;
-    } else {
-        return Err(StdError::generic_err("Time not reached yet"));
+    let multiplier = rarity_multiplier(storage, &token_info.rarity)?;
+    let ready_at =
+        token_info.reward_start_time + multiplier.waiting_time(reward_token.mining_waiting_time);
+    if ready_at >= env.block.time.seconds() {
+        return Err(ContractError::MiningNotReady {
+            token_id: token_id.to_string(),
+            ready_at,
+        });
     }
-    USER_ENERGY_LEVEL.save(deps.storage, info.sender.to_string(), &user_energy_level)?;
-    Ok(Response::new()
-        .add_attribute("action", "claim reward")
-        .add_attribute("sender", info.sender)
-        .add_attribute("token_id", token_id))
+    if token_info.durability < 1 {
+        return Err(ContractError::NeedsRepair {
+            token_id: token_id.to_string(),
+        });
+    }
+    if config.durability_start_time < env.block.time.seconds() {
+        token_info.durability = token_info
+            .durability
+            .saturating_sub(multiplier.durability_cost);
+    }
+
+    let now = env.block.time.seconds();
+    let emission_config = EMISSION_CONFIG.load(storage)?;
+    let mut emission_usage = load_emission_usage(storage, &emission_config, now)?;
+    let mut claimed = vec![];
+    // the main output is checked and paid first so its errors still come before any write
+    for (index, output) in reward_token.outputs_at(now).into_iter().enumerate() {
+        let is_main = index == 0;
+        // a chance pays its expected amount on every claim instead of a roll that could be
+        // predicted from the block time
+        let mut reward_amount = Uint128::from(multiplier.mining_rate(output.mining_rate))
+            .multiply_ratio(output.chance_bps, BPS_DENOMINATOR);
+        if let Some(remaining) = emission_config.remaining(&emission_usage, &output.item_name) {
+            // an exhausted budget defers the claim to the next epoch, a low one scales it down
+            if remaining.is_zero() && is_main {
+                return Err(ContractError::EmissionCapReached {
+                    item: output.item_name,
+                    next_epoch_at: (emission_usage.epoch + 1) * emission_config.epoch_length,
+                });
+            }
+            reward_amount = reward_amount.min(remaining);
+        }
+        let contract_item_amount = USER_ITEM_AMOUNT
+            .may_load(storage, (&env.contract.address, output.item_name.as_str()))?
+            .unwrap_or_default();
+        if contract_item_amount < reward_amount {
+            // a short pool pays what is left only when partial claims are enabled
+            if is_main && (!config.partial_claims || contract_item_amount.is_zero()) {
+                return Err(ContractError::InsufficientPool {
+                    item: output.item_name,
+                    available: contract_item_amount,
+                    need: reward_amount,
+                });
+            }
+            reward_amount = contract_item_amount;
+        }
+        if reward_amount.is_zero() {
+            continue;
+        }
+        save_item_amount(
+            storage,
+            &env.contract.address,
+            &output.item_name,
+            contract_item_amount - reward_amount,
+        )?;
+        add_amount_in_item_address(storage, sender, &output.item_name, reward_amount)?;
+        emission_usage.record(&output.item_name, reward_amount);
+        claimed.push((output.item_name, reward_amount));
+    }
+    if emission_config.epoch_length > 0 {
+        EMISSION_USAGE.save(storage, &emission_usage)?;
+    }
+    token_info.reward_start_time = now;
+    tokens().save(storage, token_id, &token_info)?;
+    *user_energy_level -= energy_cost;
+    Ok(claimed)
 }
 pub fn execute_revoke(
     deps: DepsMut,
@@ -1178,7 +2307,7 @@ pub fn execute_revoke(
     info: MessageInfo,
     spender: String,
     token_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     _update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
 
     Ok(Response::new()
@@ -1197,7 +2326,7 @@ pub fn _update_approvals(
     // if add == false, remove. if add == true, remove then set with this expiration
     add: bool,
     expires: Option<Expiration>,
-) -> StdResult<TokenInfo> {
+) -> Result<TokenInfo, ContractError> {
     let mut token = tokens().load(deps.storage, token_id)?;
     // ensure we have permissions
     check_can_approve(deps.as_ref(), env, info, &token)?;
@@ -1215,7 +2344,7 @@ pub fn _update_approvals(
         // reject expired data as invalid
         let expires = expires.unwrap_or_default();
         if expires.is_expired(&env.block) {
-            return Err(StdError::generic_err("Expired"));
+            return Err(ContractError::Expired {});
         }
         let approval = Approval {
             spender: spender_addr,
@@ -1237,11 +2366,11 @@ pub fn execute_approve_all(
     info: MessageInfo,
     operator: String,
     expires: Option<Expiration>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // reject expired data as invalid
     let expires = expires.unwrap_or_default();
     if expires.is_expired(&env.block) {
-        return Err(StdError::generic_err("Expired"));
+        return Err(ContractError::Expired {});
     }
 
     // set the operator for us
@@ -1259,7 +2388,7 @@ pub fn execute_revoke_all(
     _env: Env,
     info: MessageInfo,
     operator: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let operator_addr = deps.api.addr_validate(&operator)?;
     OPERATORS.remove(deps.storage, (&info.sender, &operator_addr));
 
@@ -1275,7 +2404,7 @@ fn check_can_approve(
     env: &Env,
     info: &MessageInfo,
     token: &TokenInfo,
-) -> StdResult<()> {
+) -> Result<(), ContractError> {
     // owner can approve
     if token.owner == info.sender {
         return Ok(());
@@ -1285,17 +2414,22 @@ fn check_can_approve(
     match op {
         Some(ex) => {
             if ex.is_expired(&env.block) {
-                Err(StdError::generic_err("Unauthorized"))
+                Err(ContractError::Unauthorized {})
             } else {
                 Ok(())
             }
         }
-        None => Err(StdError::generic_err("Unauthorized")),
+        None => Err(ContractError::Unauthorized {}),
     }
 }
 
 /// returns true iff the sender can transfer ownership of the token
-fn check_can_send(deps: Deps, env: &Env, info: &MessageInfo, token: &TokenInfo) -> StdResult<()> {
+fn check_can_send(
+    deps: Deps,
+    env: &Env,
+    info: &MessageInfo,
+    token: &TokenInfo,
+) -> Result<(), ContractError> {
     // owner can send
     if token.owner == info.sender {
         return Ok(());
@@ -1315,12 +2449,12 @@ fn check_can_send(deps: Deps, env: &Env, info: &MessageInfo, token: &TokenInfo)
     match op {
         Some(ex) => {
             if ex.is_expired(&env.block) {
-                Err(StdError::generic_err("Unauthorized"))
+                Err(ContractError::Unauthorized {})
             } else {
                 Ok(())
             }
         }
-        None => Err(StdError::generic_err("Unauthorized")),
+        None => Err(ContractError::Unauthorized {}),
     }
 }
 
@@ -1328,7 +2462,7 @@ fn check_can_send(deps: Deps, env: &Env, info: &MessageInfo, token: &TokenInfo)
 //     deps: DepsMut,
 //     env: Env,
 //     info: MessageInfo,
-// ) -> StdResult<Response> {
+// ) -> Result<Response, ContractError> {
 //     Ok(Response::new().add_attribute("action", "update tool level"))
 // }
 
@@ -1337,10 +2471,10 @@ fn execute_burn(
     env: Env,
     info: MessageInfo,
     token_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let token = tokens().load(deps.storage, &token_id)?;
     _check_can_send(deps.as_ref(), &env, &info, &token)?;
-    burn(deps.storage, token_id.to_string());
+    burn(deps.storage, token_id.to_string())?;
 
     Ok(Response::new()
         .add_attribute("action", "burn")
@@ -1348,9 +2482,14 @@ fn execute_burn(
         .add_attribute("token_id", token_id))
 }
 
-pub fn burn(store: &mut dyn Storage, token_id: String) {
-    tokens().remove(store, &token_id).unwrap();
-    decrement_tokens(store).unwrap();
+pub fn burn(store: &mut dyn Storage, token_id: String) -> Result<(), ContractError> {
+    let token = tokens().load(store, &token_id)?;
+    if token.staked_by.is_some() {
+        return Err(ContractError::TokenStaked { token_id });
+    }
+    tokens().remove(store, &token_id)?;
+    decrement_tokens(store)?;
+    Ok(())
 }
 
 pub fn decrement_tokens(storage: &mut dyn Storage) -> StdResult<u64> {
@@ -1364,7 +2503,7 @@ pub fn _check_can_send(
     env: &Env,
     info: &MessageInfo,
     token: &TokenInfo,
-) -> StdResult<()> {
+) -> Result<(), ContractError> {
     // owner can send
     if token.owner == info.sender {
         return Ok(());
@@ -1384,22 +2523,75 @@ pub fn _check_can_send(
     match op {
         Some(ex) => {
             if ex.is_expired(&env.block) {
-                Err(StdError::generic_err("Unauthorized"))
+                Err(ContractError::Unauthorized {})
             } else {
                 Ok(())
             }
         }
-        None => Err(StdError::generic_err("Unauthorized")),
+        None => Err(ContractError::Unauthorized {}),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    let res = match msg {
         QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
-        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, token_id)?),
-        QueryMsg::OwnerOf { token_id } => to_binary(&query_owner_of(deps, env, token_id)?),
-        QueryMsg::AllNftInfo { token_id } => to_binary(&query_all_nft_info(deps, env, token_id)?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, env, token_id)?),
+        QueryMsg::RewardRate { tool_type, rarity } => {
+            to_binary(&query_reward_rate(deps, env, tool_type, rarity)?)
+        }
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => to_binary(&query_owner_of(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or(false),
+        )?),
+        QueryMsg::Approval {
+            token_id,
+            spender,
+            include_expired,
+        } => to_binary(&query_approval(
+            deps,
+            env,
+            token_id,
+            spender,
+            include_expired.unwrap_or(false),
+        )?),
+        QueryMsg::Approvals {
+            token_id,
+            include_expired,
+        } => to_binary(&query_approvals(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or(false),
+        )?),
+        QueryMsg::AllOperators {
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        } => to_binary(&query_all_operators(
+            deps,
+            env,
+            owner,
+            include_expired.unwrap_or(false),
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&query_all_nft_info(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or(false),
+        )?),
         QueryMsg::NumTokens {} => to_binary(&query_num_tokens(deps)?),
         QueryMsg::Tokens {
             owner,
@@ -1417,10 +2609,10 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             item_name,
         } => to_binary(&query_user_item_balance(deps, user_address, item_name)?),
         QueryMsg::UserEnergyInfo { user_address } => {
-            to_binary(&query_user_energy_info(deps, user_address)?)
+            to_binary(&query_user_energy_info(deps, env, user_address)?)
         }
         QueryMsg::UserItemInfo { user_address } => {
-            to_binary(&query_user_item_info(deps, user_address)?)
+            to_binary(&query_user_item_info(deps, env, user_address)?)
         }
         QueryMsg::UserTokenBalance { user_address } => {
             to_binary(&query_user_token_balance(deps, user_address)?)
@@ -1433,19 +2625,69 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_remaining_pack_count(deps, tool_type)?)
         }
         QueryMsg::QueryGameDevToken {} => to_binary(&query_game_dev_token(deps)?),
-    }
+        QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::EnergyConfig {} => to_binary(&ENERGY_CONFIG.load(deps.storage)?),
+        QueryMsg::EmissionConfig {} => to_binary(&EMISSION_CONFIG.load(deps.storage)?),
+        QueryMsg::EmissionBudget {} => to_binary(&query_emission_budget(deps, env)?),
+        QueryMsg::StakeholderBalances { items } => {
+            to_binary(&query_stakeholder_balances(deps, env, items)?)
+        }
+        QueryMsg::PoolStatus {} => to_binary(&query_pool_status(deps, env)?),
+        QueryMsg::RarityMultipliers { start_after, limit } => {
+            to_binary(&query_rarity_multipliers(deps, start_after, limit)?)
+        }
+        QueryMsg::WithdrawAllowance { address, item_name } => {
+            to_binary(&query_withdraw_allowance(deps, env, address, item_name)?)
+        }
+        QueryMsg::ReserveSweeps { start_after, limit } => {
+            to_binary(&query_reserve_sweeps(deps, start_after, limit)?)
+        }
+        QueryMsg::BurnedItems { start_after, limit } => {
+            to_binary(&query_burned_items(deps, start_after, limit)?)
+        }
+        QueryMsg::Distribution { start_after, limit } => {
+            to_binary(&query_distribution(deps, start_after, limit)?)
+        }
+        QueryMsg::ToolTemplates { start_after, limit } => {
+            to_binary(&query_tool_templates(deps, start_after, limit)?)
+        }
+        QueryMsg::RewardTokens { start_after, limit } => {
+            to_binary(&query_reward_tokens(deps, start_after, limit)?)
+        }
+        QueryMsg::RarityLadder { start_after, limit } => {
+            to_binary(&query_rarity_ladder(deps, start_after, limit)?)
+        }
+        QueryMsg::ItemTokens { start_after, limit } => {
+            to_binary(&query_item_tokens(deps, start_after, limit)?)
+        }
+        QueryMsg::RepairingFees { start_after, limit } => {
+            to_binary(&query_repairing_fees(deps, start_after, limit)?)
+        }
+        QueryMsg::ToolTypes {} => to_binary(&query_tool_types(deps)?),
+    };
+    Ok(res?)
 }
 
-
 fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
     CONTRACT_INFO.load(deps.storage)
 }
-fn query_user_energy_info(deps: Deps, user_address: String) -> StdResult<Uint128> {
-    if let Some(user_energy) = USER_ENERGY_LEVEL.may_load(deps.storage, user_address)? {
-        Ok(user_energy)
-    } else {
-        Ok(Uint128::zero())
-    }
+fn query_user_energy_info(
+    deps: Deps,
+    env: Env,
+    user_address: String,
+) -> StdResult<UserEnergyInfoResponse> {
+    let energy_config = ENERGY_CONFIG.load(deps.storage)?;
+    let (energy, accounted_at) = load_energy(
+        deps.storage,
+        &energy_config,
+        &user_address,
+        env.block.time.seconds(),
+    )?;
+    Ok(UserEnergyInfoResponse {
+        energy,
+        max_energy: energy_config.max_energy,
+        next_full_at: energy_config.full_at(energy, accounted_at),
+    })
 }
 
 fn query_user_item_balance(
@@ -1453,9 +2695,10 @@ fn query_user_item_balance(
     user_address: String,
     item_name: String,
 ) -> StdResult<Uint128> {
-    let mut user_item_key = user_address;
-    user_item_key.push_str(&item_name);
-    if let Some(amount) = USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key)? {
+    let user_address = deps.api.addr_validate(&user_address)?;
+    if let Some(amount) =
+        USER_ITEM_AMOUNT.may_load(deps.storage, (&user_address, item_name.as_str()))?
+    {
         Ok(amount)
     } else {
         Ok(Uint128::zero())
@@ -1464,15 +2707,15 @@ fn query_user_item_balance(
 
 fn query_remaining_all_pack_count(deps: Deps) -> StdResult<u64> {
     let mut remaining_packs = 0u64;
-    let game_dev_tokens_name = GAME_DEV_TOKENS_NAME.load(deps.storage)?;
-    for game_dev_token_name in game_dev_tokens_name {
-        remaining_packs += if let Some(tool_set) =
-            TOOL_PACK_SET.may_load(deps.storage, game_dev_token_name.to_string())?
-        {
-            tool_set.len() as u64
-        } else {
-            0u64
-        };
+    // pack sets are keyed by tool type
+    let tool_type_names = TOOL_TYPE_NAMES.may_load(deps.storage)?.unwrap_or_default();
+    for tool_type in tool_type_names {
+        remaining_packs +=
+            if let Some(tool_set) = TOOL_PACK_SET.may_load(deps.storage, tool_type.to_string())? {
+                tool_set.len() as u64
+            } else {
+                0u64
+            };
     }
     Ok(remaining_packs)
 }
@@ -1487,41 +2730,48 @@ fn query_remaining_pack_count(deps: Deps, tool_type: String) -> StdResult<u64> {
     Ok(tool_count)
 }
 
-fn query_user_token_balance(deps: Deps, user_address: String) -> StdResult<Response> {
+fn query_user_token_balance(deps: Deps, user_address: String) -> Result<Response, ContractError> {
     let mut tokens_map = vec![];
-    let game_dev_tokens_name = GAME_DEV_TOKENS_NAME.load(deps.storage)?;
-    for game_dev_token_name in game_dev_tokens_name.clone() {
-        let item_token = if let Some(item_token) =
-            ITEM_TOKEN_MAPPING.may_load(deps.storage, game_dev_token_name.to_string())?
-        {
-            item_token
+    for game_dev_token_name in registered_items(deps.storage)? {
+        let item =
+            if let Some(item) = ITEMS.may_load(deps.storage, game_dev_token_name.to_string())? {
+                item
+            } else {
+                return Err(ContractError::ItemTokenNotFound {
+                    item: game_dev_token_name,
+                });
+            };
+        let balance = if item.native {
+            deps.querier
+                .query_balance(user_address.to_string(), item.token_addr)?
+                .amount
         } else {
-            let mut error_message = String::from(game_dev_token_name.as_str());
-            error_message.push_str(" game dev token not found");
-            return Err(StdError::generic_err(error_message));
+            let amount: BalanceResponse =
+                deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: item.token_addr,
+                    msg: to_binary(&Cw20QueryMsg::Balance {
+                        address: user_address.to_string(),
+                    })?,
+                }))?;
+            amount.balance
         };
-        let amount: BalanceResponse =
-            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: item_token,
-                msg: to_binary(&Cw20QueryMsg::Balance {
-                    address: user_address.to_string(),
-                })?,
-            }))?;
 
-        tokens_map.push((game_dev_token_name.to_string(), amount.balance));
+        tokens_map.push((game_dev_token_name.to_string(), balance));
     }
 
     Ok(Response::new().add_attributes(tokens_map))
 }
 
-fn query_user_item_info(deps: Deps, user_address: String) -> StdResult<Response> {
+fn query_user_item_info(
+    deps: Deps,
+    env: Env,
+    user_address: String,
+) -> Result<Response, ContractError> {
     let mut tokens_map = vec![];
-    let game_dev_tokens_name = GAME_DEV_TOKENS_NAME.load(deps.storage)?;
-    for game_dev_token_name in game_dev_tokens_name.clone() {
-        let mut user_item_key = user_address.to_string();
-        user_item_key.push_str(game_dev_token_name.as_str());
+    let user_addr = deps.api.addr_validate(&user_address)?;
+    for game_dev_token_name in registered_items(deps.storage)? {
         let game_dev_token_amount = if let Some(game_dev_token_amount) =
-            USER_ITEM_AMOUNT.may_load(deps.storage, user_item_key)?
+            USER_ITEM_AMOUNT.may_load(deps.storage, (&user_addr, game_dev_token_name.as_str()))?
         {
             game_dev_token_amount
         } else {
@@ -1531,31 +2781,41 @@ fn query_user_item_info(deps: Deps, user_address: String) -> StdResult<Response>
         tokens_map.push((game_dev_token_name.to_string(), game_dev_token_amount));
     }
 
-    let user_energy =
-        if let Some(user_energy) = USER_ENERGY_LEVEL.may_load(deps.storage, user_address)? {
-            user_energy
-        } else {
-            Uint128::zero()
-        };
+    let energy_config = ENERGY_CONFIG.load(deps.storage)?;
+    let (user_energy, _) = load_energy(
+        deps.storage,
+        &energy_config,
+        &user_address,
+        env.block.time.seconds(),
+    )?;
     Ok(Response::new()
         .add_attributes(tokens_map)
         .add_attribute("user energy", user_energy))
 }
 
-fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
+fn query_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+) -> Result<NftInfoResponse, ContractError> {
     let info = tokens().load(deps.storage, &token_id)?;
-    let nft_reward_info = if let Some(nft_reward_info) =
-        REWARD_TOKEN.may_load(deps.storage, info.name.to_string())?
-    {
+    let nft_reward_info = if let Some(nft_reward_info) = REWARD_TOKEN.may_load(
+        deps.storage,
+        (info.tool_type.as_str(), info.rarity.as_str()),
+    )? {
         nft_reward_info
     } else {
         RewardToken {
             item_name: "None".to_string(),
             mining_rate: 0u64,
             mining_waiting_time: 0u64,
+            schedule: None,
+            extra_outputs: vec![],
         }
     };
 
+    let multiplier = rarity_multiplier(deps.storage, &info.rarity)?;
+
     let mut template_key = info.tool_type.to_string();
     template_key.push_str(info.rarity.as_str());
     let tool_template = if let Some(tool_template) =
@@ -1563,7 +2823,9 @@ fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
     {
         tool_template
     } else {
-        return Err(StdError::generic_err("No token found"));
+        return Err(ContractError::ToolTemplateNotFound {
+            template: template_key,
+        });
     };
 
     Ok(NftInfoResponse {
@@ -1573,69 +2835,121 @@ fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
             description: tool_template.description,
             image: Some(tool_template.image),
             rarity: info.rarity,
-            mining_waiting_time: nft_reward_info.mining_waiting_time,
-            mining_rate: nft_reward_info.mining_rate,
+            mining_waiting_time: multiplier.waiting_time(nft_reward_info.mining_waiting_time),
+            mining_rate: multiplier.mining_rate(nft_reward_info.rate_at(env.block.time.seconds())),
+            outputs: nft_reward_info
+                .outputs_at(env.block.time.seconds())
+                .into_iter()
+                .map(|output| RewardOutput {
+                    mining_rate: multiplier.mining_rate(output.mining_rate),
+                    ..output
+                })
+                .collect(),
             owner: info.owner.to_string(),
         },
     })
 }
 
-fn query_owner_of(deps: Deps, env: Env, token_id: String) -> StdResult<OwnerOfResponse> {
+fn query_owner_of(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<OwnerOfResponse> {
     let info = tokens().load(deps.storage, &token_id)?;
     Ok(OwnerOfResponse {
         owner: info.owner.to_string(),
-        approvals: humanize_approvals(&env.block, &info),
+        approvals: humanize_approvals(&env.block, &info, include_expired),
     })
 }
 
-fn query_all_nft_info(deps: Deps, env: Env, token_id: String) -> StdResult<AllNftInfoResponse> {
+fn query_approval(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    spender: String,
+    include_expired: bool,
+) -> Result<ApprovalResponse, ContractError> {
     let info = tokens().load(deps.storage, &token_id)?;
-    let nft_reward_info = if let Some(nft_reward_info) =
-        REWARD_TOKEN.may_load(deps.storage, info.name.to_string())?
-    {
-        nft_reward_info
-    } else {
-        RewardToken {
-            item_name: "None".to_string(),
-            mining_rate: 0u64,
-            mining_waiting_time: 0u64,
-        }
-    };
+    let approval = humanize_approvals(&env.block, &info, include_expired)
+        .into_iter()
+        .find(|apr| apr.spender == spender);
+    match approval {
+        Some(approval) => Ok(ApprovalResponse { approval }),
+        None => Err(ContractError::ApprovalNotFound { spender }),
+    }
+}
 
-    let mut template_key = info.tool_type.to_string();
-    template_key.push_str(info.rarity.as_str());
-    let tool_template = if let Some(tool_template) =
-        TOOL_TEMPLATE_MAP.may_load(deps.storage, template_key.to_string())?
-    {
-        tool_template
-    } else {
-        return Err(StdError::generic_err("No token found"));
-    };
+fn query_approvals(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<ApprovalsResponse> {
+    let info = tokens().load(deps.storage, &token_id)?;
+    Ok(ApprovalsResponse {
+        approvals: humanize_approvals(&env.block, &info, include_expired),
+    })
+}
 
-    Ok(AllNftInfoResponse {
-        access: OwnerOfResponse {
-            owner: info.owner.to_string(),
-            approvals: humanize_approvals(&env.block, &info),
-        },
-        info: NftInfoResponse {
-            token_uri: tool_template.token_uri,
-            extension: Extension {
-                name: info.name,
-                description: tool_template.description,
-                image: Some(tool_template.image),
-                rarity: info.rarity,
-                mining_waiting_time: nft_reward_info.mining_waiting_time,
-                mining_rate: nft_reward_info.mining_rate,
-                owner: info.owner.to_string(),
-            },
-        },
+fn query_all_operators(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    include_expired: bool,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = maybe_addr(deps.api, start_after)?;
+    let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let operators: StdResult<Vec<cw721::Approval>> = OPERATORS
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, expires)) => include_expired || !expires.is_expired(&env.block),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| -> StdResult<cw721::Approval> {
+            let (k, expires) = item?;
+            let spender = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+            Ok(cw721::Approval { spender, expires })
+        })
+        .collect();
+    Ok(OperatorsResponse {
+        operators: operators?,
+    })
+}
+
+fn query_minter(deps: Deps) -> StdResult<MinterResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(MinterResponse {
+        minter: config.minter,
     })
 }
 
-fn humanize_approvals(block: &BlockInfo, info: &TokenInfo) -> Vec<cw721::Approval> {
+fn query_all_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> Result<AllNftInfoResponse, ContractError> {
+    let access = query_owner_of(deps, env.clone(), token_id.to_string(), include_expired)?;
+    let info = query_nft_info(deps, env, token_id)?;
+    Ok(AllNftInfoResponse { access, info })
+}
+
+fn humanize_approvals(
+    block: &BlockInfo,
+    info: &TokenInfo,
+    include_expired: bool,
+) -> Vec<cw721::Approval> {
     info.approvals
         .iter()
-        .filter(|apr| !apr.is_expired(block))
+        .filter(|apr| include_expired || !apr.is_expired(block))
         .map(humanize_approval)
         .collect()
 }
@@ -1681,8 +2995,7 @@ fn query_all_tokens(
     limit: Option<u32>,
 ) -> StdResult<TokensResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start_addr = maybe_addr(deps.api, start_after)?;
-    let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+    let start = start_after.map(Bound::exclusive);
 
     let tokens: StdResult<Vec<String>> = tokens()
         .range(deps.storage, start, None, Order::Ascending)
@@ -1704,32 +3017,639 @@ fn query_user_staked_info(deps: Deps, user_address: String) -> StdResult<HashSet
 }
 
 fn query_game_dev_token(deps: Deps) -> StdResult<Vec<String>> {
-    GAME_DEV_TOKENS_NAME.load(deps.storage)
+    registered_items(deps.storage)
+}
+
+/// paginates over one of the string keyed registries set by the minter
+fn range_registry<T>(
+    deps: Deps,
+    registry: &Map<String, T>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, T)>>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    registry
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| -> StdResult<(String, T)> {
+            let (k, value) = item?;
+            let key = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn query_tool_templates(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ToolTemplatesResponse> {
+    let templates = range_registry(deps, &TOOL_TEMPLATE_MAP, start_after, limit)?
+        .into_iter()
+        .map(|(template_key, template)| ToolTemplateInfo {
+            template_key,
+            template,
+        })
+        .collect();
+    Ok(ToolTemplatesResponse { templates })
+}
+
+fn query_reward_tokens(
+    deps: Deps,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> StdResult<RewardTokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|(tool_type, rarity)| {
+        Bound::exclusive((tool_type.as_str(), rarity.as_str()).joined_key())
+    });
+    let reward_tokens = REWARD_TOKEN
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| -> StdResult<RewardTokenInfo> {
+            let (k, reward_token) = item?;
+            let (tool_type, rarity) = parse_pair_key(&k)?;
+            Ok(RewardTokenInfo {
+                tool_type,
+                rarity,
+                reward_token,
+            })
+        })
+        .collect::<StdResult<Vec<RewardTokenInfo>>>()?;
+    Ok(RewardTokensResponse { reward_tokens })
+}
+
+fn query_rarity_ladder(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RarityLadderResponse> {
+    let rarities = range_registry(deps, &RARITY_TYPES, start_after, limit)?
+        .into_iter()
+        .map(|(rarity, upgraded_rarity)| RarityStep {
+            rarity,
+            upgraded_rarity,
+        })
+        .collect();
+    Ok(RarityLadderResponse { rarities })
+}
+
+fn query_item_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ItemTokensResponse> {
+    let items = range_registry(deps, &ITEMS, start_after, limit)?
+        .into_iter()
+        .map(|(item_name, item)| ItemTokenInfo {
+            item_name,
+            token_addr: item.token_addr,
+            decimals: item.decimals,
+            enabled: item.enabled,
+            native: item.native,
+        })
+        .collect();
+    Ok(ItemTokensResponse { items })
+}
+
+fn query_repairing_fees(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RepairingFeesResponse> {
+    let fees = range_registry(deps, &REPAIRING_FEE, start_after, limit)?
+        .into_iter()
+        .map(|(rarity, fee)| RepairingFeeInfo { rarity, fee })
+        .collect();
+    Ok(RepairingFeesResponse { fees })
+}
+
+fn query_distribution(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DistributionResponse> {
+    let shares = DISTRIBUTION.load(deps.storage)?;
+    let item_overrides = range_registry(deps, &ITEM_DISTRIBUTION, start_after, limit)?
+        .into_iter()
+        .map(|(item_name, shares)| ItemDistributionInfo { item_name, shares })
+        .collect();
+    Ok(DistributionResponse {
+        shares,
+        item_overrides,
+    })
+}
+
+fn query_stakeholder_balances(
+    deps: Deps,
+    env: Env,
+    items: Option<Vec<String>>,
+) -> StdResult<StakeholderBalancesResponse> {
+    let stakeholders = stakeholder_balances(deps.storage, &env, items)?
+        .into_iter()
+        .map(|(address, item_balances)| StakeholderBalance {
+            address: address.to_string(),
+            items: item_balances
+                .into_iter()
+                .map(|(item_name, amount)| ItemAmount { item_name, amount })
+                .collect(),
+        })
+        .collect();
+    Ok(StakeholderBalancesResponse { stakeholders })
+}
+
+fn query_reward_rate(
+    deps: Deps,
+    env: Env,
+    tool_type: String,
+    rarity: String,
+) -> Result<RewardRateResponse, ContractError> {
+    let reward_token = if let Some(reward_token) =
+        REWARD_TOKEN.may_load(deps.storage, (tool_type.as_str(), rarity.as_str()))?
+    {
+        reward_token
+    } else {
+        return Err(ContractError::RewardTokenNotFound { tool_type, rarity });
+    };
+    let now = env.block.time.seconds();
+    let next_change = reward_token.next_change(now);
+    Ok(RewardRateResponse {
+        current_rate: reward_token.rate_at(now),
+        next_change_at: next_change.map(|(changes_at, _)| changes_at),
+        next_rate: next_change.map(|(_, next_rate)| next_rate),
+        item_name: reward_token.item_name,
+        base_rate: reward_token.mining_rate,
+    })
+}
+
+/// emission left in the current epoch for every capped or already mined item
+fn query_emission_budget(deps: Deps, env: Env) -> StdResult<EmissionBudgetResponse> {
+    let emission_config = EMISSION_CONFIG.load(deps.storage)?;
+    let emission_usage =
+        load_emission_usage(deps.storage, &emission_config, env.block.time.seconds())?;
+    let mut item_names: Vec<String> = emission_config
+        .item_caps
+        .iter()
+        .map(|c| c.item_name.to_string())
+        .collect();
+    for (item_name, _) in emission_usage.items.iter() {
+        if !item_names.contains(item_name) {
+            item_names.push(item_name.to_string());
+        }
+    }
+    let items = item_names
+        .into_iter()
+        .map(|item_name| ItemEmissionBudget {
+            emitted: emission_usage.item_amount(&item_name),
+            remaining: emission_config.remaining(&emission_usage, &item_name),
+            item_name,
+        })
+        .collect();
+    let epoch_ends_at = if emission_config.epoch_length == 0 {
+        None
+    } else {
+        Some((emission_usage.epoch + 1) * emission_config.epoch_length)
+    };
+    Ok(EmissionBudgetResponse {
+        epoch: emission_usage.epoch,
+        epoch_ends_at,
+        emitted: emission_usage.total,
+        global_remaining: emission_config
+            .global_cap
+            .filter(|_| emission_config.epoch_length > 0)
+            .map(|cap| cap.saturating_sub(emission_usage.total)),
+        items,
+    })
+}
+
+/// pool balance of every reward item against what the staked tools mine per day
+fn query_pool_status(deps: Deps, env: Env) -> StdResult<PoolStatusResponse> {
+    let reward_tokens = REWARD_TOKEN
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| -> StdResult<((String, String), RewardToken)> {
+            let (k, reward_token) = item?;
+            Ok((parse_pair_key(&k)?, reward_token))
+        })
+        .collect::<StdResult<Vec<((String, String), RewardToken)>>>()?;
+    let mut item_names = registered_items(deps.storage)?;
+    let now = env.block.time.seconds();
+    for (_, reward_token) in reward_tokens.iter() {
+        for output in reward_token.outputs_at(now) {
+            if !item_names.contains(&output.item_name) {
+                item_names.push(output.item_name);
+            }
+        }
+    }
+
+    let mut items = vec![];
+    for item_name in item_names {
+        let mut daily_emission = Uint128::zero();
+        for ((tool_type, rarity), reward_token) in reward_tokens.iter() {
+            let staked_count = STAKED_TOOL_COUNT
+                .may_load(deps.storage, (tool_type.as_str(), rarity.as_str()))?
+                .unwrap_or_default();
+            let multiplier = rarity_multiplier(deps.storage, rarity)?;
+            let waiting_time = multiplier.waiting_time(reward_token.mining_waiting_time);
+            for output in reward_token.outputs_at(now) {
+                if output.item_name != item_name {
+                    continue;
+                }
+                // outputs with a chance count with the share they pay on every claim
+                let mining_rate = Uint128::from(multiplier.mining_rate(output.mining_rate))
+                    .multiply_ratio(output.chance_bps, BPS_DENOMINATOR);
+                daily_emission += (mining_rate * Uint128::from(staked_count))
+                    .multiply_ratio(SECONDS_PER_DAY, waiting_time.max(1));
+            }
+        }
+        let pool_amount = USER_ITEM_AMOUNT
+            .may_load(deps.storage, (&env.contract.address, item_name.as_str()))?
+            .unwrap_or_default();
+        let runway_days = if daily_emission.is_zero() {
+            None
+        } else {
+            Some((pool_amount / daily_emission).u128() as u64)
+        };
+        items.push(PoolItemStatus {
+            item_name,
+            pool_amount,
+            daily_emission,
+            runway_days,
+        });
+    }
+    Ok(PoolStatusResponse { items })
+}
+
+fn query_rarity_multipliers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RarityMultipliersResponse> {
+    let multipliers = range_registry(deps, &RARITY_MULTIPLIERS, start_after, limit)?
+        .into_iter()
+        .map(|(rarity, multiplier)| RarityMultiplierInfo { rarity, multiplier })
+        .collect();
+    Ok(RarityMultipliersResponse { multipliers })
+}
+
+fn query_withdraw_allowance(
+    deps: Deps,
+    env: Env,
+    address: String,
+    item_name: String,
+) -> StdResult<WithdrawAllowanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let policy = WITHDRAW_POLICIES
+        .may_load(deps.storage, item_name.to_string())?
+        .unwrap_or_default();
+    let usage = policy.current_usage(
+        USER_WITHDRAWALS.may_load(deps.storage, (&address, item_name.as_str()))?,
+        env.block.time.seconds(),
+    );
+    Ok(WithdrawAllowanceResponse {
+        item_name,
+        limit: policy.limit,
+        withdrawn: usage.withdrawn(),
+        remaining: policy.remaining(&usage),
+        frees_at: policy.frees_at(&usage),
+        fee_bps: policy.fee_bps,
+    })
+}
+
+fn query_reserve_sweeps(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ReserveSweepsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::from(id)));
+    let sweeps = RESERVE_SWEEPS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| -> StdResult<ReserveSweepInfo> {
+            let (k, sweep) = item?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&k);
+            Ok(ReserveSweepInfo {
+                id: u64::from_be_bytes(id),
+                swept_at: sweep.swept_at,
+                recipient: sweep.recipient,
+                items: sweep
+                    .items
+                    .into_iter()
+                    .map(|(item_name, amount)| ItemAmount { item_name, amount })
+                    .collect(),
+            })
+        })
+        .collect::<StdResult<Vec<ReserveSweepInfo>>>()?;
+    Ok(ReserveSweepsResponse { sweeps })
+}
+
+fn query_burned_items(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BurnedItemsResponse> {
+    let items = range_registry(deps, &BURNED_ITEMS, start_after, limit)?
+        .into_iter()
+        .map(|(item_name, amount)| BurnedItemInfo { item_name, amount })
+        .collect();
+    Ok(BurnedItemsResponse { items })
+}
+
+fn query_tool_types(deps: Deps) -> StdResult<ToolTypesResponse> {
+    let tool_types = TOOL_TYPE_NAMES.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ToolTypesResponse { tool_types })
 }
 
 // #[cfg_attr(not(feature = "library"), entry_point)]
-// pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+// pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
 //     Ok(Response::default())
 // }
 
-
-
 #[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let ver = cw2::get_contract_version(deps.storage)?;
     // ensure we are migrating from an allowed contract
     if ver.contract != CONTRACT_NAME {
-        return Err(StdError::generic_err("Can only upgrade from same type").into());
+        return Err(ContractError::CannotMigrate {});
     }
     // note: better to do proper semver compare, but string compare *usually* works
     // if ver.version >= CONTRACT_VERSION.to_string() {
     //     return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
     // }
-    
+
     // set the new version
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    
-    // do any desired state migrations...
-    
-    Ok(Response::default())
-}
\ No newline at end of file
+
+    // register items mapped to a token before the item registry
+    migrate_item_registry(deps.storage)?;
+    // move balances and repair kits from concatenated string keys to tuple keys
+    migrate_legacy_item_keys(deps.storage)?;
+    // the registry lists the game dev tokens now
+    LEGACY_GAME_DEV_TOKENS_NAME.remove(deps.storage);
+    // burn shares credited to the burn address move to the pending burns
+    migrate_pending_burns(deps.storage)?;
+    // count the item supplies once all balances are in place
+    migrate_item_supply(deps.storage)?;
+    // deployments from before the energy config keep the previously hardcoded values
+    if ENERGY_CONFIG.may_load(deps.storage)?.is_none() {
+        ENERGY_CONFIG.save(deps.storage, &EnergyConfig::default())?;
+    }
+    if DISTRIBUTION.may_load(deps.storage)?.is_none() {
+        DISTRIBUTION.save(deps.storage, &default_distribution())?;
+    }
+    if EMISSION_CONFIG.may_load(deps.storage)?.is_none() {
+        EMISSION_CONFIG.save(deps.storage, &EmissionConfig::default())?;
+    }
+    // record the staker on tokens staked before staking state lived on the token
+    migrate_staked_tokens(deps.storage)?;
+    // attach rewards keyed by tool name to the templates using that name
+    let dropped_rewards = migrate_reward_tokens(deps.storage)?;
+    // name the items of positional template costs
+    let legacy_template_items = msg.legacy_template_items.unwrap_or_else(|| {
+        LEGACY_TEMPLATE_ITEMS
+            .iter()
+            .map(|item_name| item_name.to_string())
+            .collect()
+    });
+    migrate_template_costs(deps.storage, &legacy_template_items)?;
+
+    let mut response = Response::new().add_attribute("action", "migrate");
+    if !dropped_rewards.is_empty() {
+        response = response.add_attribute("dropped_rewards", dropped_rewards.join(","));
+    }
+    Ok(response)
+}
+
+/// sets staked_by and staked_at on tokens listed in USER_STAKED_INFO and counts staked tools
+/// when no count was kept yet
+fn migrate_staked_tokens(storage: &mut dyn Storage) -> StdResult<()> {
+    let count_tools = STAKED_TOOL_COUNT
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_none();
+    let staked_infos = USER_STAKED_INFO
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, HashSet<String>)>>>()?;
+    for (staker, token_ids) in staked_infos {
+        let staker = String::from_utf8(staker).map_err(StdError::invalid_utf8)?;
+        for token_id in token_ids {
+            if let Some(mut token) = tokens().may_load(storage, &token_id)? {
+                if token.staked_by.is_none() {
+                    token.staked_by = Some(Addr::unchecked(&staker));
+                    token.staked_at = Some(token.reward_start_time);
+                    tokens().save(storage, &token_id, &token)?;
+                }
+                if count_tools {
+                    count_staked_tool(storage, &token, true)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// moves rewards keyed by tool name to the (tool_type, rarity) of every template with that name,
+/// name keyed rewards without a template could not be mined, they are dropped and their names
+/// returned so the migration reports them
+fn migrate_reward_tokens(storage: &mut dyn Storage) -> StdResult<Vec<String>> {
+    let templates = TOOL_TEMPLATE_MAP
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, ToolTemplate)>>>()?;
+    let mut matched_names: HashSet<String> = HashSet::new();
+    for (template_key, template) in templates {
+        let template_key = String::from_utf8(template_key).map_err(StdError::invalid_utf8)?;
+        // template keys are tool_type + rarity
+        let tool_type = if let Some(tool_type) = template_key.strip_suffix(&template.rarity) {
+            tool_type
+        } else {
+            continue;
+        };
+        let key = (tool_type, template.rarity.as_str());
+        matched_names.insert(template.name.to_string());
+        if REWARD_TOKEN.may_load(storage, key)?.is_some() {
+            continue;
+        }
+        if let Some(reward_token) =
+            LEGACY_REWARD_TOKEN.may_load(storage, template.name.to_string())?
+        {
+            REWARD_TOKEN.save(storage, key, &reward_token)?;
+        }
+    }
+
+    let legacy_tools = LEGACY_REWARD_TOKEN
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<Vec<Vec<u8>>>();
+    let mut dropped = vec![];
+    for tool_name in legacy_tools {
+        let tool_name = String::from_utf8(tool_name).map_err(StdError::invalid_utf8)?;
+        LEGACY_REWARD_TOKEN.remove(storage, tool_name.to_string());
+        if !matched_names.contains(&tool_name) {
+            dropped.push(tool_name);
+        }
+    }
+    Ok(dropped)
+}
+
+/// moves item token addresses into the item registry as enabled items with default decimals
+fn migrate_item_registry(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_items = LEGACY_ITEM_TOKEN_MAPPING
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, String)>>>()?;
+    for (item_name, token_addr) in legacy_items {
+        let item_name = String::from_utf8(item_name).map_err(StdError::invalid_utf8)?;
+        if ITEMS.may_load(storage, item_name.to_string())?.is_none() {
+            let item = ItemInfo {
+                token_addr,
+                decimals: DEFAULT_ITEM_DECIMALS,
+                enabled: true,
+                native: false,
+            };
+            ITEMS.save(storage, item_name.to_string(), &item)?;
+        }
+        LEGACY_ITEM_TOKEN_MAPPING.remove(storage, item_name);
+    }
+    Ok(())
+}
+
+/// moves the balances of the burn address into the pending burns, unless the burn address
+/// also holds another role and its balances can not be told apart
+fn migrate_pending_burns(storage: &mut dyn Storage) -> StdResult<()> {
+    let config = CONFIG.load(storage)?;
+    if [
+        &config.team_addr,
+        &config.market_addr,
+        &config.legal_addr,
+        &config.reserve_addr,
+    ]
+    .contains(&&config.burn_addr)
+    {
+        return Ok(());
+    }
+    let burn_addr = Addr::unchecked(&config.burn_addr);
+    let burn_balances = USER_ITEM_AMOUNT
+        .prefix(&burn_addr)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, Uint128)>>>()?;
+    for (item_name, amount) in burn_balances {
+        let item_name = String::from_utf8(item_name).map_err(StdError::invalid_utf8)?;
+        let pending = PENDING_BURNS
+            .may_load(storage, &item_name)?
+            .unwrap_or_default();
+        PENDING_BURNS.save(storage, &item_name, &(pending + amount))?;
+        save_item_amount(storage, &burn_addr, &item_name, Uint128::zero())?;
+    }
+    Ok(())
+}
+
+/// sums the balances of every item into its supply
+fn migrate_item_supply(storage: &mut dyn Storage) -> StdResult<()> {
+    let balances = USER_ITEM_AMOUNT
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, Uint128)>>>()?;
+    let mut supplies: Vec<(String, Uint128)> = vec![];
+    for (key, amount) in balances {
+        let (_, item_name) = parse_pair_key(&key)?;
+        match supplies.iter_mut().find(|(name, _)| *name == item_name) {
+            Some((_, supply)) => *supply += amount,
+            None => supplies.push((item_name, amount)),
+        }
+    }
+    for (item_name, supply) in supplies {
+        ITEM_SUPPLY.save(storage, &item_name, &supply)?;
+    }
+    Ok(())
+}
+
+/// zips positional template costs with the given item order, every non zero cost has to
+/// land on a registered item
+fn migrate_template_costs(
+    storage: &mut dyn Storage,
+    legacy_items: &[String],
+) -> Result<(), ContractError> {
+    let templates = TOOL_TEMPLATE_MAP
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, ToolTemplate)>>>()?;
+    for (template_key, mut template) in templates {
+        if template.required_amount.is_empty() {
+            continue;
+        }
+        let template_key = String::from_utf8(template_key).map_err(StdError::invalid_utf8)?;
+        for (index, amount) in template.required_amount.into_iter().enumerate() {
+            if amount.is_zero() {
+                continue;
+            }
+            let item_name = if let Some(item_name) = legacy_items.get(index) {
+                item_name
+            } else {
+                return Err(ContractError::UnmappedLegacyCost {
+                    template: template_key,
+                    index,
+                });
+            };
+            if ITEMS.may_load(storage, item_name.to_string())?.is_none() {
+                return Err(ContractError::ItemTokenNotFound {
+                    item: item_name.to_string(),
+                });
+            }
+            template
+                .required_items
+                .push((item_name.to_string(), amount));
+        }
+        template.required_amount = vec![];
+        TOOL_TEMPLATE_MAP.save(storage, template_key, &template)?;
+    }
+    Ok(())
+}
+
+/// rewrites `address + item` and `address + "RepKit" + tool_type` keys into (address, name) keys
+fn migrate_legacy_item_keys(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut item_names = ITEMS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| -> StdResult<String> {
+            let (k, _) = item?;
+            String::from_utf8(k).map_err(StdError::invalid_utf8)
+        })
+        .collect::<StdResult<Vec<String>>>()?;
+    item_names.extend(
+        LEGACY_GAME_DEV_TOKENS_NAME
+            .may_load(storage)?
+            .unwrap_or_default(),
+    );
+    item_names.sort();
+    item_names.dedup();
+    // longest names first so a shorter item name never claims a longer one's suffix
+    item_names.sort_by(|a, b| b.len().cmp(&a.len()));
+
+    let legacy_balances = LEGACY_USER_ITEM_AMOUNT
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, Uint128)>>>()?;
+    for (key, amount) in legacy_balances {
+        let key = String::from_utf8(key).map_err(StdError::invalid_utf8)?;
+        LEGACY_USER_ITEM_AMOUNT.remove(storage, key.to_string());
+        // entries without an address part or a known item suffix were written under wrong keys
+        if let Some(item_name) = item_names
+            .iter()
+            .find(|name| key.len() > name.len() && key.ends_with(name.as_str()))
+        {
+            let addr = Addr::unchecked(&key[..key.len() - item_name.len()]);
+            add_amount_in_item_address(storage, &addr, item_name, amount)?;
+        }
+    }
+
+    let legacy_repair_kits = LEGACY_USER_REPAIR_KITS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, String)>>>()?;
+    for (key, token_id) in legacy_repair_kits {
+        let key = String::from_utf8(key).map_err(StdError::invalid_utf8)?;
+        LEGACY_USER_REPAIR_KITS.remove(storage, key.to_string());
+        if let Some((addr, tool_type)) = key.split_once(REPAIR_KIT_KEYWORD) {
+            USER_REPAIR_KITS.save(storage, (&Addr::unchecked(addr), tool_type), &token_id)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Approval has already expired")]
+    Expired {},
+
+    #[error("No approval found for {spender}")]
+    ApprovalNotFound { spender: String },
+
+    #[error("Unknown hook message")]
+    UnknownHookMsg {},
+
+    #[error("Can only upgrade from same type")]
+    CannotMigrate {},
+
+    #[error("Batch mint requires a minting count")]
+    MintingCountMissing {},
+
+    #[error("Token {token_id} not found")]
+    TokenNotFound { token_id: String },
+
+    #[error("Token {token_id} already exists")]
+    TokenAlreadyExists { token_id: String },
+
+    #[error("Token {token_id} is a pack token")]
+    IsPackToken { token_id: String },
+
+    #[error("Token {token_id} is not a pack token")]
+    NotPackToken { token_id: String },
+
+    #[error("Tool type {tool_type} already exists")]
+    ToolTypeExists { tool_type: String },
+
+    #[error("Tool template {template} not found")]
+    ToolTemplateNotFound { template: String },
+
+    #[error("No reward token found for {rarity} {tool_type}")]
+    RewardTokenNotFound { tool_type: String, rarity: String },
+
+    #[error("No token contract found for item {item}")]
+    ItemTokenNotFound { item: String },
+
+    #[error("Token contract {token_addr} is not mapped to an item")]
+    UnknownItemToken { token_addr: String },
+
+    #[error("Denom {denom} is not mapped to a native item")]
+    UnknownDenom { denom: String },
+
+    #[error("No funds sent")]
+    NoFundsSent {},
+
+    #[error("Item {item} is a native coin and has no cw20 token")]
+    NativeItem { item: String },
+
+    #[error("Legacy cost {index} of template {template} has no item in the migration order")]
+    UnmappedLegacyCost { template: String, index: usize },
+
+    #[error("Item {item} is disabled")]
+    ItemDisabled { item: String },
+
+    #[error("Item {item} is listed more than once")]
+    DuplicateItem { item: String },
+
+    #[error("Item {item} is still used by {used_by}")]
+    ItemInUse { item: String, used_by: String },
+
+    #[error("Insufficient {item}: have {have}, need {need}")]
+    InsufficientItem {
+        item: String,
+        have: Uint128,
+        need: Uint128,
+    },
+
+    #[error("Insufficient energy: have {have}, need {need}")]
+    InsufficientEnergy { have: Uint128, need: Uint128 },
+
+    #[error("Energy would exceed the cap of {max}")]
+    EnergyCapExceeded { max: Uint128 },
+
+    #[error("Fee of {bps} bps is above 10000 bps")]
+    InvalidFee { bps: u64 },
+
+    #[error("Withdraw policy needs a fee below 10000 bps and a window for its limit")]
+    InvalidWithdrawPolicy {},
+
+    #[error("Only {remaining} {item} can be withdrawn until {frees_at}")]
+    WithdrawLimitReached {
+        item: String,
+        remaining: Uint128,
+        frees_at: u64,
+    },
+
+    #[error("Distribution shares must sum to 10000 bps, got {total_bps}")]
+    InvalidDistribution { total_bps: u64 },
+
+    #[error("Emission cap of {item} reached for this epoch, next epoch starts at {next_epoch_at}")]
+    EmissionCapReached { item: String, next_epoch_at: u64 },
+
+    #[error("Rate schedule needs ascending halvings, a non zero interval and at most 10000 bps")]
+    InvalidRateSchedule {},
+
+    #[error("Reward output {item} has a chance above 10000 bps")]
+    InvalidRewardOutput { item: String },
+
+    #[error("Contract pool has {available} {item}, reward needs {need}")]
+    InsufficientPool {
+        item: String,
+        available: Uint128,
+        need: Uint128,
+    },
+
+    #[error("Stake limit of {limit} tools reached")]
+    StakeLimitReached { limit: u64 },
+
+    #[error("Token {token_id} is staked")]
+    TokenStaked { token_id: String },
+
+    #[error("Token {token_id} is not staked by sender")]
+    TokenNotStaked { token_id: String },
+
+    #[error("Mining not ready for token {token_id}, ready at {ready_at}")]
+    MiningNotReady { token_id: String, ready_at: u64 },
+
+    #[error("Token {token_id} must be repaired before claiming")]
+    NeedsRepair { token_id: String },
+
+    #[error("Token {token_id} is at full durability")]
+    ToolNotDamaged { token_id: String },
+
+    #[error("Repair kit for {tool_type} is already deployed")]
+    RepairKitAlreadyDeployed { tool_type: String },
+
+    #[error("No repair kit deployed for {tool_type}")]
+    RepairKitNotDeployed { tool_type: String },
+
+    #[error("Repair kit {token_id} not ready, ready at {ready_at}")]
+    RepairKitNotReady { token_id: String, ready_at: u64 },
+
+    #[error("Repairing fee is not set for {key}")]
+    RepairingFeeNotSet { key: String },
+
+    #[error("No tool pack available for {tool_type}")]
+    NoPackAvailable { tool_type: String },
+
+    #[error("No tool set found for {tool_type}")]
+    ToolSetNotFound { tool_type: String },
+
+    #[error("No tool available to distribute")]
+    NoToolAvailable {},
+
+    #[error("Upgrade requires {expected} tokens, got {got}")]
+    InvalidUpgradeCount { expected: usize, got: usize },
+
+    #[error("Upgrade requires tokens of the same rarity")]
+    RarityMismatch {},
+
+    #[error("Upgrade requires tokens of the same tool type")]
+    ToolTypeMismatch {},
+
+    #[error("No upgrade available for rarity {rarity}")]
+    UpgradeNotAvailable { rarity: String },
+}
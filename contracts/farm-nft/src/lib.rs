@@ -1,4 +1,5 @@
 pub mod contract;
+mod error;
 pub mod mint;
 pub mod msg;
 pub mod state;
@@ -7,3 +8,5 @@ mod testing;
 
 #[cfg(test)]
 pub mod mock;
+
+pub use crate::error::ContractError;
@@ -1,10 +1,11 @@
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Storage, Uint128};
 
 use crate::contract::{_check_can_send, burn};
+use crate::error::ContractError;
 use crate::msg::MintMsg;
 use crate::state::{
-    distribute_amount, increment_tokens, tokens, TokenInfo, CONFIG, GAME_DEV_TOKENS_NAME,
+    distribute_amount, increment_tokens, save_item_amount, tokens, TokenInfo, CONFIG,
     LAST_GEN_TOKEN_ID, PACK_KEYWORD, RARITY_TYPES, REPAIR_KIT_KEYWORD, REPAIR_KIT_SET,
     TOOL_PACK_SET, TOOL_SET_MAP, TOOL_TEMPLATE_MAP, USER_ITEM_AMOUNT,
 };
@@ -15,21 +16,25 @@ pub fn execute_batch_mint(
     env: Env,
     info: MessageInfo,
     msg: MintMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     if info.sender != config.minter {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
+    let minting_count = if let Some(minting_count) = msg.minting_count {
+        minting_count
+    } else {
+        return Err(ContractError::MintingCountMissing {});
+    };
     let mut number = 0u64;
     let mut token_ids: String = String::new();
     // create the token
-    while number < msg.minting_count.unwrap() {
-        token_ids.push_str(mint(deps.storage, &env, &msg).to_string().as_str());
+    while number < minting_count {
+        token_ids.push_str(mint(deps.storage, &env, &msg)?.to_string().as_str());
         token_ids.push_str(" ,");
         number += 1;
     }
-    println!("tool_type {} , token id {}", msg.tool_type, token_ids);
     Ok(Response::new()
         .add_attribute("action", "mint")
         .add_attribute("minter", info.sender)
@@ -44,15 +49,15 @@ pub fn execute_mint(
     env: Env,
     info: MessageInfo,
     msg: MintMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     if info.sender != config.minter {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     // create the token
-    let token_id = mint(deps.storage, &env, &msg);
+    let token_id = mint(deps.storage, &env, &msg)?;
 
     Ok(Response::new()
         .add_attribute("action", "mint")
@@ -63,12 +68,17 @@ pub fn execute_mint(
 }
 
 ///minitng functionality
-pub fn mint(store: &mut dyn Storage, env: &Env, msg: &MintMsg) -> u64 {
+pub fn mint(store: &mut dyn Storage, env: &Env, msg: &MintMsg) -> Result<u64, ContractError> {
     let mut template_key = msg.tool_type.to_string();
     template_key.push_str(msg.rarity.to_string().as_str());
-    let tool_template = TOOL_TEMPLATE_MAP
-        .load(store, template_key.to_string())
-        .unwrap();
+    let tool_template =
+        if let Some(tool_template) = TOOL_TEMPLATE_MAP.may_load(store, template_key.to_string())? {
+            tool_template
+        } else {
+            return Err(ContractError::ToolTemplateNotFound {
+                template: template_key,
+            });
+        };
 
     let mut token = TokenInfo {
         name: msg.name.to_string(),
@@ -82,64 +92,45 @@ pub fn mint(store: &mut dyn Storage, env: &Env, msg: &MintMsg) -> u64 {
         durability: tool_template.durability,
         is_repair_kit: false,
         repair_kit_available_time: env.block.time.seconds(),
+        staked_by: None,
+        staked_at: None,
     };
-    increment_tokens(store).unwrap();
-    let last_gen_token_id = LAST_GEN_TOKEN_ID.load(store).unwrap();
+    increment_tokens(store)?;
+    let last_gen_token_id = LAST_GEN_TOKEN_ID.may_load(store)?.unwrap_or_default();
     let new_toke_id = last_gen_token_id + 1;
     //save last generated token id
-    LAST_GEN_TOKEN_ID.save(store, &new_toke_id).unwrap();
+    LAST_GEN_TOKEN_ID.save(store, &new_toke_id)?;
 
     //if contract is the owner than nft it means user has opened a pack
     if msg.owner == env.contract.address {
-        let mut token_ids = if let Some(token_ids) = TOOL_SET_MAP
-            .may_load(store, msg.tool_type.to_string())
-            .unwrap()
-        {
-            token_ids
-        } else {
-            vec![]
-        };
+        let mut token_ids = TOOL_SET_MAP
+            .may_load(store, msg.tool_type.to_string())?
+            .unwrap_or_default();
         token_ids.push(new_toke_id.to_string());
         //saving in tool set map
-        TOOL_SET_MAP
-            .save(store, msg.tool_type.to_string(), &token_ids)
-            .unwrap();
+        TOOL_SET_MAP.save(store, msg.tool_type.to_string(), &token_ids)?;
     } else if msg.rarity.eq_ignore_ascii_case(PACK_KEYWORD) {
-        let mut tool_pack_set = if let Some(tool_pack_set) = TOOL_PACK_SET
-            .may_load(store, msg.tool_type.to_string())
-            .unwrap()
-        {
-            tool_pack_set
-        } else {
-            vec![]
-        };
+        let mut tool_pack_set = TOOL_PACK_SET
+            .may_load(store, msg.tool_type.to_string())?
+            .unwrap_or_default();
         tool_pack_set.push(new_toke_id.to_string());
-        TOOL_PACK_SET
-            .save(store, msg.tool_type.to_string(), &tool_pack_set)
-            .unwrap();
+        TOOL_PACK_SET.save(store, msg.tool_type.to_string(), &tool_pack_set)?;
         token.is_pack_token = true;
     } else if msg.rarity.eq_ignore_ascii_case(REPAIR_KIT_KEYWORD) {
-        let mut repair_kit_set = if let Some(repair_kit_set) = REPAIR_KIT_SET
-            .may_load(store, msg.tool_type.to_string())
-            .unwrap()
-        {
-            repair_kit_set
-        } else {
-            vec![]
-        };
+        let mut repair_kit_set = REPAIR_KIT_SET
+            .may_load(store, msg.tool_type.to_string())?
+            .unwrap_or_default();
         repair_kit_set.push(new_toke_id.to_string());
-        REPAIR_KIT_SET
-            .save(store, msg.tool_type.to_string(), &repair_kit_set)
-            .unwrap();
+        REPAIR_KIT_SET.save(store, msg.tool_type.to_string(), &repair_kit_set)?;
         token.is_repair_kit = true;
     }
-    tokens()
-        .update(store, &new_toke_id.to_string(), |old| match old {
-            Some(_) => Err(StdError::generic_err("Claimed")),
-            None => Ok(token),
-        })
-        .unwrap();
-    new_toke_id
+    tokens().update(store, &new_toke_id.to_string(), |old| match old {
+        Some(_) => Err(ContractError::TokenAlreadyExists {
+            token_id: new_toke_id.to_string(),
+        }),
+        None => Ok(token),
+    })?;
+    Ok(new_toke_id)
 }
 /// mint common rarity type NFT in exchange game dev tokens
 pub fn execute_mint_common_nft(
@@ -147,7 +138,7 @@ pub fn execute_mint_common_nft(
     env: Env,
     info: MessageInfo,
     tool_type: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut template_key = tool_type.to_string();
     template_key.push_str("Common");
@@ -156,7 +147,9 @@ pub fn execute_mint_common_nft(
     {
         tool_template
     } else {
-        return Err(StdError::generic_err("Not found"));
+        return Err(ContractError::ToolTemplateNotFound {
+            template: template_key,
+        });
     };
     let msg = MintMsg {
         owner: deps.api.addr_validate(&info.sender.to_string())?,
@@ -166,48 +159,27 @@ pub fn execute_mint_common_nft(
         minting_count: None,
         tool_type,
     };
-    let game_dev_token_set = GAME_DEV_TOKENS_NAME.load(deps.storage)?;
-    for (index, game_dev_token_name) in game_dev_token_set.into_iter().enumerate() {
-        let mut user_addr = String::from(&info.sender.to_string());
-        user_addr.push_str(&game_dev_token_name);
+    for (item_name, amount) in tool_template.required_items {
         let mut item_required_amount = if let Some(item_required_amount) =
-            USER_ITEM_AMOUNT.may_load(deps.storage, user_addr.to_string())?
+            USER_ITEM_AMOUNT.may_load(deps.storage, (&info.sender, item_name.as_str()))?
         {
             item_required_amount
         } else {
             Uint128::zero()
         };
-        if item_required_amount < *tool_template.required_amount.get(index).unwrap() {
-            let mut message = String::from("dev_token_name: ");
-            message.push_str(&game_dev_token_name.to_string());
-            message.push_str(" item_required_amount: ");
-            message.push_str(&item_required_amount.to_string());
-            message.push_str(" tool_template.required_amount.get(index).unwrap(): ");
-            message.push_str(
-                &tool_template
-                    .required_amount
-                    .get(index)
-                    .unwrap()
-                    .to_string(),
-            );
-            message.push_str(" index: ");
-            message.push_str(&index.to_string());
-            message.push_str(" InSufficient funds ");
-            return Err(StdError::generic_err(message));
+        if item_required_amount < amount {
+            return Err(ContractError::InsufficientItem {
+                item: item_name,
+                have: item_required_amount,
+                need: amount,
+            });
         }
-        item_required_amount -= *tool_template.required_amount.get(index).unwrap();
-        let amount = *tool_template.required_amount.get(index).unwrap();
-        distribute_amount(
-            deps.storage,
-            game_dev_token_name.to_string(),
-            amount,
-            &config,
-            &env,
-        );
-        USER_ITEM_AMOUNT.save(deps.storage, user_addr.to_string(), &item_required_amount)?;
+        item_required_amount -= amount;
+        save_item_amount(deps.storage, &info.sender, &item_name, item_required_amount)?;
+        distribute_amount(deps.storage, item_name.to_string(), amount, &config, &env)?;
     }
 
-    mint(deps.storage, &env, &msg);
+    mint(deps.storage, &env, &msg)?;
 
     Ok(Response::new())
 }
@@ -218,18 +190,23 @@ pub fn execute_mint_upgraded_nft(
     env: Env,
     info: MessageInfo,
     token_ids: Vec<String>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let mut token_rarity = "".to_string();
     let mut tool_type = "".to_string();
     if token_ids.len() != 5 {
-        return Err(StdError::generic_err("Not eligible"));
+        return Err(ContractError::InvalidUpgradeCount {
+            expected: 5,
+            got: token_ids.len(),
+        });
     }
 
     for token_id in token_ids.iter() {
         let token = if let Some(token) = tokens().may_load(deps.storage, token_id)? {
             token
         } else {
-            return Err(StdError::generic_err("No token found"));
+            return Err(ContractError::TokenNotFound {
+                token_id: token_id.to_string(),
+            });
         };
         let mut template_key = token.tool_type.to_string();
         template_key.push_str(token.rarity.to_string().as_str());
@@ -237,26 +214,25 @@ pub fn execute_mint_upgraded_nft(
         if token_rarity.is_empty() {
             token_rarity.push_str(&token.rarity);
         } else if token_rarity != token.rarity {
-            return Err(StdError::generic_err(
-                "Not eligible, kindly provide same rarity tokens",
-            ));
+            return Err(ContractError::RarityMismatch {});
         }
         if tool_type.is_empty() {
             tool_type.push_str(&token.tool_type);
         } else if tool_type != token.tool_type {
-            return Err(StdError::generic_err(
-                "Not eligible, kindly provide same tool type tokens",
-            ));
+            return Err(ContractError::ToolTypeMismatch {});
         }
         _check_can_send(deps.as_ref(), &env, &info, &token)?;
-        burn(deps.storage, token_id.to_string());
+        burn(deps.storage, token_id.to_string())?;
     }
-    let upgraded_token_rarity =
-        if let Some(upgraded_token_rarity) = RARITY_TYPES.may_load(deps.storage, token_rarity)? {
-            upgraded_token_rarity
-        } else {
-            return Err(StdError::generic_err("No upgraded token found"));
-        };
+    let upgraded_token_rarity = if let Some(upgraded_token_rarity) =
+        RARITY_TYPES.may_load(deps.storage, token_rarity.to_string())?
+    {
+        upgraded_token_rarity
+    } else {
+        return Err(ContractError::UpgradeNotAvailable {
+            rarity: token_rarity,
+        });
+    };
     let mut template_key = tool_type.to_string();
     template_key.push_str(&upgraded_token_rarity);
     let tool_template = if let Some(tool_template) =
@@ -264,7 +240,9 @@ pub fn execute_mint_upgraded_nft(
     {
         tool_template
     } else {
-        return Err(StdError::generic_err("No tool template found"));
+        return Err(ContractError::ToolTemplateNotFound {
+            template: template_key,
+        });
     };
     let msg = MintMsg {
         owner: deps.api.addr_validate(&info.sender.to_string())?,
@@ -274,6 +252,6 @@ pub fn execute_mint_upgraded_nft(
         minting_count: None,
         tool_type,
     };
-    mint(deps.storage, &env, &msg);
+    mint(deps.storage, &env, &msg)?;
     Ok(Response::new())
 }
@@ -1,9 +1,14 @@
 use cosmwasm_std::{Addr, Binary, Uint128};
 use cw20::Cw20ReceiveMsg;
-use cw721::{Cw721ReceiveMsg, OwnerOfResponse};
+use cw721::{Cw721ReceiveMsg, Expiration, OwnerOfResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::state::{
+    ClaimEnergyCost, DistributionShare, ItemEmissionCap, RarityMultiplier, RateSchedule,
+    RewardOutput, RewardToken, ToolTemplate, WithdrawPolicy,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// Name of the NFT contract
@@ -43,6 +48,41 @@ pub struct UpdateConfigMsg {
     pub durability_from_start_time: Option<u64>,
 
     pub reserve_addr: Option<String>,
+
+    pub pool_floor: Option<Uint128>,
+
+    pub partial_claims: Option<bool>,
+
+    pub transfer_fee_bps: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateEnergyConfigMsg {
+    pub starter_energy: Option<Uint128>,
+
+    pub claim_cost: Option<Uint128>,
+    /// replaces all claim cost overrides
+    pub claim_cost_overrides: Option<Vec<ClaimEnergyCost>>,
+
+    pub refill_item: Option<String>,
+
+    pub refill_ratio: Option<Uint128>,
+    /// zero removes the cap
+    pub max_energy: Option<Uint128>,
+
+    pub regen_amount: Option<Uint128>,
+    /// zero disables regeneration
+    pub regen_interval: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateEmissionConfigMsg {
+    /// zero disables the caps
+    pub epoch_length: Option<u64>,
+    /// zero removes the cap
+    pub global_cap: Option<Uint128>,
+    /// replaces all item caps
+    pub item_caps: Option<Vec<ItemEmissionCap>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -58,7 +98,6 @@ pub struct MintMsg {
     pub minting_count: Option<u64>,
 
     pub tool_type: String, //common tool name
-
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BoostMsg {
@@ -97,31 +136,75 @@ pub enum ExecuteMsg {
         msg: Binary,
     },
 
+    /// Allows operator to transfer / send the token from the owner's account.
+    /// If expiration is set, then this allowance has a time/height limit
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+
+    /// Remove previously granted Approval
+    Revoke {
+        spender: String,
+        token_id: String,
+    },
+
+    /// Allows operator to transfer / send any token from the owner's account.
+    /// If expiration is set, then this allowance has a time/height limit
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+
+    /// Remove previously granted ApproveAll permission
+    RevokeAll {
+        operator: String,
+    },
+
     ReceiveNft(Cw721ReceiveMsg),
 
     ClaimReward {
         token_id: String,
     },
 
+    /// claim every ready tool in `token_ids`, or all staked tools of the sender when None
+    ClaimRewards {
+        token_ids: Option<Vec<String>>,
+    },
+
     Unstake {
         token_id: String,
     },
 
+    UnstakeMany {
+        token_ids: Vec<String>,
+    },
+
     Receive(Cw20ReceiveMsg),
 
+    /// Set the item mined by tools of a tool type and rarity, `schedule` lowers the mining
+    /// rate over time and `extra_outputs` adds further items mined on each claim with a
+    /// chance. Needed before the tool template can be added
     AddRewardToken {
         item_name: String,
-        tool_name: String,
+        tool_type: String,
+        rarity: String,
         mining_rate: u64,
         mining_waiting_time: u64,
+        schedule: Option<RateSchedule>,
+        extra_outputs: Option<Vec<RewardOutput>>,
     },
     AddToolTypeNames {
         tool_type: String,
     },
     BatchMint(MintMsg),
+    /// Register an item with its cw20 token or move a registered item to another token,
+    /// `decimals` defaults to 6 for a new item
     AddItemToken {
         item_name: String,
         item_token_addr: String,
+        decimals: Option<u8>,
     },
     RefillEnergy {
         food_item_amount: u64,
@@ -130,6 +213,18 @@ pub enum ExecuteMsg {
         item_name: String,
         amount: Uint128,
     },
+    /// Move in-game item balance to another address, `transfer_fee_bps` of the amount is
+    /// distributed like spent items
+    TransferItem {
+        recipient: String,
+        item_name: String,
+        amount: Uint128,
+    },
+    /// Move several in-game item balances to another address at once
+    SendItemBatch {
+        recipient: String,
+        items: Vec<ItemAmount>,
+    },
     AddToolTemplate(ToolTemplateMsg),
     MintCommonNft {
         tool_type: String,
@@ -140,14 +235,87 @@ pub enum ExecuteMsg {
 
     UpdateConfig(UpdateConfigMsg),
 
-    TransferReserveAmount {},
+    UpdateEnergyConfig(UpdateEnergyConfigMsg),
+
+    /// Set the per epoch emission caps, claims beyond a cap are scaled down or deferred
+    UpdateEmissionConfig(UpdateEmissionConfigMsg),
+
+    /// Scale mining rate, waiting time and durability cost of all tools of a rarity
+    SetRarityMultiplier {
+        rarity: String,
+        multiplier: RarityMultiplier,
+    },
+
+    /// Set the withdrawal limit per rolling window and the withdrawal fee of an item
+    SetWithdrawPolicy {
+        item_name: String,
+        policy: WithdrawPolicy,
+    },
+
+    /// Remove the withdraw policy of an item so it can be withdrawn without limit or fee
+    RemoveWithdrawPolicy {
+        item_name: String,
+    },
+
+    /// Set how spent items are split between stakeholders, only for `item_name` when given.
+    /// Shares must sum to 10000 bps
+    SetDistribution {
+        item_name: Option<String>,
+        shares: Vec<DistributionShare>,
+    },
+
+    /// Burn the item tokens of the pending burn shares, callable by anyone
+    FlushBurns {},
+
+    /// Transfer the accrued item balances of team, market and legal to them as item tokens,
+    /// every game dev token is paid out when `items` is not given
+    PayoutStakeholders {
+        items: Option<Vec<String>>,
+    },
+
+    /// Remove the split override of an item so it uses the default split again
+    RemoveItemDistribution {
+        item_name: String,
+    },
+
+    /// Send the contract pool above `pool_floor` to the reserve address, for every game dev
+    /// token unless `items` is given and at most `amount` of each item when given
+    TransferReserveAmount {
+        items: Option<Vec<String>>,
+        amount: Option<Uint128>,
+    },
 
     TransferToolPack {
         recipient: String,
         tool_type: String,
     },
 
-    AddItemName {
+    /// Add a game dev token to the item registry, fails if the item was added before
+    AddItem {
+        item_name: String,
+        item_token_addr: String,
+        decimals: Option<u8>,
+    },
+    /// Add a chain native coin as game dev token, deposited with `Deposit` and withdrawn
+    /// as bank coins
+    AddNativeItem {
+        item_name: String,
+        denom: String,
+        decimals: Option<u8>,
+    },
+    /// Credit the native coins sent along to the in-game balances of the sender
+    Deposit {},
+    /// Stop deposits of an item and its use in new tool templates
+    DisableItem {
+        item_name: String,
+    },
+    /// Allow deposits of a disabled item and its use in new tool templates again
+    EnableItem {
+        item_name: String,
+    },
+    /// Remove an item no tool template, reward token, energy refill, withdraw policy or
+    /// balance uses anymore
+    RemoveItem {
         item_name: String,
     },
 
@@ -177,6 +345,33 @@ pub enum QueryMsg {
     /// Return type: OwnerOfResponse
     OwnerOf {
         token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+    },
+
+    /// Return operator that can access a single token, error if no such approval exists.
+    /// Return type: `ApprovalResponse`
+    Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+
+    /// Return all approvals that are set on the given token.
+    /// Return type: `ApprovalsResponse`
+    Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+
+    /// List all operators that can access all of the owner's tokens.
+    /// Return type: `OperatorsResponse`
+    AllOperators {
+        owner: String,
+        /// unset or false will filter out expired items, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
 
     /// Total number of tokens issued
@@ -198,8 +393,14 @@ pub enum QueryMsg {
     /// for clients: `AllNftInfo`
     AllNftInfo {
         token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
     },
 
+    /// Return the minter
+    /// Return type: `MinterResponse`
+    Minter {},
+
     /// With Enumerable extension.
     /// Returns all tokens owned by the given address, [] if unset.
     /// Return type: TokensResponse.
@@ -224,6 +425,8 @@ pub enum QueryMsg {
         user_address: String,
         item_name: String,
     },
+    /// Returns the current energy of a user with its cap and regeneration status
+    /// Return type: `UserEnergyInfoResponse`
     UserEnergyInfo {
         user_address: String,
     },
@@ -239,6 +442,114 @@ pub enum QueryMsg {
     },
 
     QueryGameDevToken {},
+
+    /// Returns the contract configuration set at instantiate / update config
+    /// Return type: `Config`
+    Config {},
+
+    /// Returns the default split of spent items and the per item overrides
+    /// Return type: `DistributionResponse`
+    Distribution {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns the accrued but unpaid item balances of team, market and legal
+    /// Return type: `StakeholderBalancesResponse`
+    StakeholderBalances {
+        items: Option<Vec<String>>,
+    },
+
+    /// Returns the contract pool balance, the daily emission of staked tools and the
+    /// days of runway left for every reward item
+    /// Return type: `PoolStatusResponse`
+    PoolStatus {},
+
+    /// Returns the history of reserve sweeps, oldest first
+    /// Return type: `ReserveSweepsResponse`
+    ReserveSweeps {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Returns the cumulative amount burned on chain per item
+    /// Return type: `BurnedItemsResponse`
+    BurnedItems {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns the energy economy parameters
+    /// Return type: `EnergyConfig`
+    EnergyConfig {},
+
+    /// Returns the mining rate of a tool right now and its next scheduled change
+    /// Return type: `RewardRateResponse`
+    RewardRate {
+        tool_type: String,
+        rarity: String,
+    },
+
+    /// Returns the multipliers set per rarity, rarities not listed use the reward token values
+    /// Return type: `RarityMultipliersResponse`
+    RarityMultipliers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns how much of an item the address can still withdraw and when its oldest withdrawal leaves the window
+    /// Return type: `WithdrawAllowanceResponse`
+    WithdrawAllowance {
+        address: String,
+        item_name: String,
+    },
+
+    /// Returns the per epoch emission caps
+    /// Return type: `EmissionConfig`
+    EmissionConfig {},
+
+    /// Returns the emission mined and left in the current epoch
+    /// Return type: `EmissionBudgetResponse`
+    EmissionBudget {},
+
+    /// Lists registered tool templates keyed by tool type and rarity
+    /// Return type: `ToolTemplatesResponse`
+    ToolTemplates {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists reward tokens keyed by (tool type, rarity)
+    /// Return type: `RewardTokensResponse`
+    RewardTokens {
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
+
+    /// Lists rarity upgrade steps (e.g. Common -> Uncommon)
+    /// Return type: `RarityLadderResponse`
+    RarityLadder {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists the registered game items with their cw20 token addresses
+    /// Return type: `ItemTokensResponse`
+    ItemTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists repairing fees
+    /// Return type: `RepairingFeesResponse`
+    RepairingFees {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns all registered tool type names
+    /// Return type: `ToolTypesResponse`
+    ToolTypes {},
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -258,6 +569,7 @@ pub struct Extension {
     pub rarity: String,
     pub mining_rate: u64,
     pub mining_waiting_time: u64,
+    pub outputs: Vec<RewardOutput>, //every item the tool mines, the main item first
     pub owner: String,
 }
 
@@ -275,6 +587,200 @@ pub struct AllNftInfoResponse {
     pub info: NftInfoResponse,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ToolTemplateInfo {
+    /// tool type concatenated with rarity, as used by mint
+    pub template_key: String,
+    pub template: ToolTemplate,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ToolTemplatesResponse {
+    pub templates: Vec<ToolTemplateInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardTokenInfo {
+    pub tool_type: String,
+    pub rarity: String,
+    pub reward_token: RewardToken,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardTokensResponse {
+    pub reward_tokens: Vec<RewardTokenInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RarityStep {
+    pub rarity: String,
+    pub upgraded_rarity: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RarityLadderResponse {
+    pub rarities: Vec<RarityStep>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemTokenInfo {
+    pub item_name: String,
+    pub token_addr: String,
+    pub decimals: u8,
+    pub enabled: bool,
+    pub native: bool, //token_addr is a bank denom
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemTokensResponse {
+    pub items: Vec<ItemTokenInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RepairingFeeInfo {
+    pub rarity: String,
+    pub fee: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RepairingFeesResponse {
+    pub fees: Vec<RepairingFeeInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ToolTypesResponse {
+    pub tool_types: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemDistributionInfo {
+    pub item_name: String,
+    pub shares: Vec<DistributionShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DistributionResponse {
+    pub shares: Vec<DistributionShare>,
+    pub item_overrides: Vec<ItemDistributionInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemAmount {
+    pub item_name: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakeholderBalance {
+    pub address: String,
+    pub items: Vec<ItemAmount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakeholderBalancesResponse {
+    pub stakeholders: Vec<StakeholderBalance>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RarityMultiplierInfo {
+    pub rarity: String,
+    pub multiplier: RarityMultiplier,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RarityMultipliersResponse {
+    pub multipliers: Vec<RarityMultiplierInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct WithdrawAllowanceResponse {
+    pub item_name: String,
+    /// None when withdrawals of the item are unlimited
+    pub limit: Option<Uint128>,
+    pub withdrawn: Uint128,
+    pub remaining: Option<Uint128>,
+    /// time the oldest withdrawal in the window expires and frees its amount, None without
+    /// withdrawals in the window
+    pub frees_at: Option<u64>,
+    pub fee_bps: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardRateResponse {
+    pub item_name: String,
+    pub base_rate: u64,
+    pub current_rate: u64,
+    /// None when the rate no longer changes
+    pub next_change_at: Option<u64>,
+    pub next_rate: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemEmissionBudget {
+    pub item_name: String,
+    pub emitted: Uint128,
+    /// None when nothing caps the item
+    pub remaining: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EmissionBudgetResponse {
+    pub epoch: u64,
+    /// None when the caps are disabled
+    pub epoch_ends_at: Option<u64>,
+    pub emitted: Uint128,
+    pub global_remaining: Option<Uint128>,
+    pub items: Vec<ItemEmissionBudget>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PoolItemStatus {
+    pub item_name: String,
+    pub pool_amount: Uint128,
+    pub daily_emission: Uint128,
+    /// None when nothing staked mines the item
+    pub runway_days: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PoolStatusResponse {
+    pub items: Vec<PoolItemStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReserveSweepInfo {
+    pub id: u64,
+    pub swept_at: u64,
+    pub recipient: String,
+    pub items: Vec<ItemAmount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReserveSweepsResponse {
+    pub sweeps: Vec<ReserveSweepInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BurnedItemInfo {
+    pub item_name: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BurnedItemsResponse {
+    pub items: Vec<BurnedItemInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UserEnergyInfoResponse {
+    /// energy including regeneration accrued up to the current block
+    pub energy: Uint128,
+    pub max_energy: Option<Uint128>,
+    /// time in seconds when regeneration reaches max_energy, None when energy does not regenerate or
+    /// never fills within a u64 timestamp
+    pub next_full_at: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw721HookMsg {
@@ -284,6 +790,11 @@ pub enum Cw721HookMsg {
     OpenPack {},
 
     StakeRepairKit {},
+    /// stake the sent token together with `token_ids`, which the contract pulls
+    /// from the sender through an Approve or ApproveAll granted to the contract
+    StakeMany {
+        token_ids: Vec<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -307,12 +818,14 @@ pub struct ToolTemplateMsg {
     pub description: String,
     pub image: String,
     pub rarity: String,
-    pub required_gwood_amount: Uint128,
-    pub required_gfood_amount: Uint128,
-    pub required_ggold_amount: Uint128,
-    pub required_gstone_amount: Uint128,
+    pub required_items: Vec<(String, Uint128)>, //registered item name and amount paid to mint
     pub durability: u64,
     pub token_uri: Option<String>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// items paid by the positional costs of old tool templates, in order, defaults to
+    /// gWood, gFood, gGold and gStone
+    #[serde(default)]
+    pub legacy_template_items: Option<Vec<String>>,
+}
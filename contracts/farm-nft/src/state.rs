@@ -1,7 +1,7 @@
 /// maps token_id to its level
-use cosmwasm_std::{Addr, BlockInfo, Env, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, BlockInfo, Env, Order, StdError, StdResult, Storage, Uint128};
 use cw721::{ContractInfoResponse, Expiration};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -33,7 +33,12 @@ pub struct TokenInfo {
     pub is_repair_kit: bool,
 
     pub repair_kit_available_time: u64,
-
+    /// address that staked this token, the contract holds the token while it is set
+    #[serde(default)]
+    pub staked_by: Option<Addr>,
+    /// time in seconds when the token was staked
+    #[serde(default)]
+    pub staked_at: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -102,6 +107,204 @@ pub struct Config {
     pub durability_start_time: u64, //start time of deducing durability
     pub reserve_addr: String, //reserve address for contract pool funds
     pub repair_kit_waiting_time: u64,
+    #[serde(default)]
+    pub pool_floor: Uint128, //pool amount of every item kept back from reserve sweeps
+    #[serde(default)]
+    pub partial_claims: bool, //pay out what is left when the pool is short instead of failing the claim
+    #[serde(default)]
+    pub transfer_fee_bps: u64, //share of in-game transfers split between the stakeholders, 0 for free transfers
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReserveSweep {
+    pub swept_at: u64,                 //block time of the sweep
+    pub recipient: String,             //reserve address the items were sent to
+    pub items: Vec<(String, Uint128)>, //item names with the swept amounts
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EnergyConfig {
+    pub starter_energy: Uint128, //energy granted when a user stakes for the first time
+    pub claim_cost: Uint128,     //energy spent per claim when no override matches
+    pub claim_cost_overrides: Vec<ClaimEnergyCost>, //claim costs for specific tool types and rarities
+    pub refill_item: String,                        //item spent to refill energy e.g. gFood
+    pub refill_ratio: Uint128,                      //energy gained per refill item
+    pub max_energy: Option<Uint128>,                //energy level cap, no cap when None
+    #[serde(default)]
+    pub regen_amount: Uint128, //energy regenerated every regen_interval up to max_energy
+    #[serde(default)]
+    pub regen_interval: u64,   //seconds per regeneration step, zero disables regeneration
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimEnergyCost {
+    pub tool_type: Option<String>, //matches every tool type when None
+    pub rarity: Option<String>,    //matches every rarity when None
+    pub cost: Uint128,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        EnergyConfig {
+            starter_energy: Uint128::from(200u128),
+            claim_cost: Uint128::from(3u128),
+            claim_cost_overrides: vec![],
+            refill_item: "gFood".to_string(),
+            refill_ratio: Uint128::from(3u128),
+            max_energy: None,
+            regen_amount: Uint128::zero(),
+            regen_interval: 0,
+        }
+    }
+}
+
+impl EnergyConfig {
+    /// energy spent to claim with a tool, the override matching both tool type and rarity wins
+    pub fn claim_cost(&self, tool_type: &str, rarity: &str) -> Uint128 {
+        self.claim_cost_overrides
+            .iter()
+            .filter(|o| o.tool_type.as_deref().map_or(true, |t| t == tool_type))
+            .filter(|o| o.rarity.as_deref().map_or(true, |r| r == rarity))
+            .max_by_key(|o| (o.tool_type.is_some(), o.rarity.is_some()))
+            .map_or(self.claim_cost, |o| o.cost)
+    }
+
+    /// regeneration only runs towards a cap
+    fn regen_cap(&self) -> Option<Uint128> {
+        if self.regen_amount.is_zero() || self.regen_interval == 0 {
+            return None;
+        }
+        self.max_energy
+    }
+
+    /// energy after the regeneration accrued from `updated_at` to `now`, together with the time
+    /// the energy is accounted up to so a partially elapsed interval is kept for the next read
+    pub fn regenerate(&self, energy: Uint128, updated_at: u64, now: u64) -> (Uint128, u64) {
+        let regen_cap = match self.regen_cap() {
+            Some(regen_cap) if energy < regen_cap => regen_cap,
+            _ => return (energy, now),
+        };
+        let intervals = now.saturating_sub(updated_at) / self.regen_interval;
+        let regenerated = self
+            .regen_amount
+            .checked_mul(Uint128::from(intervals))
+            .unwrap_or(Uint128::MAX);
+        let energy = energy.saturating_add(regenerated).min(regen_cap);
+        if energy == regen_cap {
+            (energy, now)
+        } else {
+            (energy, updated_at + intervals * self.regen_interval)
+        }
+    }
+
+    /// time at which regeneration fills the energy up to max_energy, None without regeneration
+    /// or when that time does not fit in a u64
+    pub fn full_at(&self, energy: Uint128, accounted_at: u64) -> Option<u64> {
+        let regen_cap = self.regen_cap()?;
+        if energy >= regen_cap {
+            return Some(accounted_at);
+        }
+        let missing = (regen_cap - energy).u128();
+        let regen_amount = self.regen_amount.u128();
+        let mut steps = missing / regen_amount;
+        if missing % regen_amount != 0 {
+            steps += 1;
+        }
+        u64::try_from(steps)
+            .ok()?
+            .checked_mul(self.regen_interval)?
+            .checked_add(accounted_at)
+    }
+
+    /// caps an energy level at max_energy
+    pub fn cap(&self, energy: Uint128) -> Uint128 {
+        match self.max_energy {
+            Some(max_energy) => energy.min(max_energy),
+            None => energy,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct EmissionConfig {
+    pub epoch_length: u64, //seconds per emission epoch, zero disables the caps
+    pub global_cap: Option<Uint128>, //amount of all items together mined per epoch, no cap when None
+    pub item_caps: Vec<ItemEmissionCap>, //amount of a single item mined per epoch
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemEmissionCap {
+    pub item_name: String,
+    pub cap: Uint128,
+}
+
+impl EmissionConfig {
+    /// index of the epoch containing `now`
+    pub fn epoch(&self, now: u64) -> u64 {
+        if self.epoch_length == 0 {
+            0
+        } else {
+            now / self.epoch_length
+        }
+    }
+
+    /// amount of an item that can still be mined this epoch, None when nothing caps it
+    pub fn remaining(&self, usage: &EmissionUsage, item_name: &str) -> Option<Uint128> {
+        if self.epoch_length == 0 {
+            return None;
+        }
+        let global_remaining = self.global_cap.map(|cap| cap.saturating_sub(usage.total));
+        let item_remaining = self
+            .item_caps
+            .iter()
+            .find(|c| c.item_name == item_name)
+            .map(|c| c.cap.saturating_sub(usage.item_amount(item_name)));
+        match (global_remaining, item_remaining) {
+            (Some(global), Some(item)) => Some(global.min(item)),
+            (global, item) => global.or(item),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct EmissionUsage {
+    pub epoch: u64,                    //epoch the amounts were mined in
+    pub total: Uint128,                //amount of all items mined this epoch
+    pub items: Vec<(String, Uint128)>, //amount mined this epoch per item
+}
+
+impl EmissionUsage {
+    pub fn item_amount(&self, item_name: &str) -> Uint128 {
+        self.items
+            .iter()
+            .find(|(name, _)| name == item_name)
+            .map(|(_, amount)| *amount)
+            .unwrap_or_default()
+    }
+
+    pub fn record(&mut self, item_name: &str, amount: Uint128) {
+        self.total += amount;
+        match self.items.iter_mut().find(|(name, _)| name == item_name) {
+            Some((_, mined)) => *mined += amount,
+            None => self.items.push((item_name.to_string(), amount)),
+        }
+    }
+}
+
+/// emission of the current epoch, starting empty once a new epoch begins
+pub fn load_emission_usage(
+    store: &dyn Storage,
+    emission_config: &EmissionConfig,
+    now: u64,
+) -> StdResult<EmissionUsage> {
+    let epoch = emission_config.epoch(now);
+    match EMISSION_USAGE.may_load(store)? {
+        Some(usage) if usage.epoch == epoch => Ok(usage),
+        _ => Ok(EmissionUsage {
+            epoch,
+            ..EmissionUsage::default()
+        }),
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -109,6 +312,342 @@ pub struct RewardToken {
     pub item_name: String,        //items name e.g. wood, gold e.t.c
     pub mining_rate: u64,         //its a rate to earn item amount
     pub mining_waiting_time: u64, //its a waiting time to make task complete
+    #[serde(default)]
+    pub schedule: Option<RateSchedule>, //lowers the mining rate over time, fixed rate when None
+    #[serde(default)]
+    pub extra_outputs: Vec<RewardOutput>, //further items mined with the same waiting time and schedule
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardOutput {
+    pub item_name: String,
+    pub mining_rate: u64, //amount mined per claim before schedule and rarity multiplier
+    pub chance_bps: u64,  //share of mining_rate paid on every claim, 10000 pays the full rate
+}
+
+impl RewardToken {
+    /// mining rate at `now` once the schedule is applied
+    pub fn rate_at(&self, now: u64) -> u64 {
+        match &self.schedule {
+            Some(schedule) => schedule.apply(self.mining_rate, now),
+            None => self.mining_rate,
+        }
+    }
+
+    /// every output of the tool with its rate at `now`, the main item first
+    pub fn outputs_at(&self, now: u64) -> Vec<RewardOutput> {
+        let mut outputs = vec![RewardOutput {
+            item_name: self.item_name.to_string(),
+            mining_rate: self.rate_at(now),
+            chance_bps: BPS_DENOMINATOR,
+        }];
+        for output in self.extra_outputs.iter() {
+            let mining_rate = match &self.schedule {
+                Some(schedule) => schedule.apply(output.mining_rate, now),
+                None => output.mining_rate,
+            };
+            outputs.push(RewardOutput {
+                mining_rate,
+                ..output.clone()
+            });
+        }
+        outputs
+    }
+
+    /// time and rate of the next scheduled change, None when the rate stays as it is
+    pub fn next_change(&self, now: u64) -> Option<(u64, u64)> {
+        let schedule = self.schedule.as_ref()?;
+        let rate = self.rate_at(now);
+        let mut changes_at = schedule.next_change_at(now)?;
+        // rounding can keep a step flat, skip ahead until the rate moves
+        loop {
+            let next_rate = schedule.apply(self.mining_rate, changes_at);
+            if next_rate != rate {
+                return Some((changes_at, next_rate));
+            }
+            if schedule.is_settled(self.mining_rate, changes_at) {
+                return None;
+            }
+            changes_at = schedule.next_change_at(changes_at)?;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RateSchedule {
+    /// halves the rate at every listed timestamp
+    Halvings { at: Vec<u64> },
+    /// lowers the rate by `amount` every `interval` seconds after `start`, never below `floor`
+    Linear {
+        start: u64,
+        interval: u64,
+        amount: u64,
+        floor: u64,
+    },
+    /// keeps `bps` of the rate every `interval` seconds after `start`, never below `floor`
+    Exponential {
+        start: u64,
+        interval: u64,
+        bps: u64,
+        floor: u64,
+    },
+}
+
+const DECAY_PRECISION: u128 = 1_000_000_000;
+
+impl RateSchedule {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            RateSchedule::Halvings { at } => at.windows(2).all(|w| w[0] < w[1]),
+            RateSchedule::Linear { interval, .. } => *interval > 0,
+            RateSchedule::Exponential { interval, bps, .. } => {
+                *interval > 0 && *bps <= BPS_DENOMINATOR
+            }
+        }
+    }
+
+    /// rate derived from `base_rate` with every change up to `now` applied
+    pub fn apply(&self, base_rate: u64, now: u64) -> u64 {
+        match self {
+            RateSchedule::Halvings { at } => {
+                let halvings = at.iter().filter(|t| **t <= now).count() as u32;
+                base_rate.checked_shr(halvings).unwrap_or(0)
+            }
+            RateSchedule::Linear {
+                start,
+                interval,
+                amount,
+                floor,
+            } => {
+                let steps = now.saturating_sub(*start) / interval;
+                base_rate
+                    .saturating_sub(steps.saturating_mul(*amount))
+                    .max((*floor).min(base_rate))
+            }
+            RateSchedule::Exponential {
+                start,
+                interval,
+                bps,
+                floor,
+            } => {
+                let steps = now.saturating_sub(*start) / interval;
+                // fixed point power by squaring, the factor never exceeds the precision
+                let mut factor = DECAY_PRECISION;
+                let mut base = *bps as u128 * DECAY_PRECISION / BPS_DENOMINATOR as u128;
+                let mut steps = steps;
+                while steps > 0 && factor > 0 {
+                    if steps & 1 == 1 {
+                        factor = factor * base / DECAY_PRECISION;
+                    }
+                    base = base * base / DECAY_PRECISION;
+                    steps >>= 1;
+                }
+                let rate = (base_rate as u128 * factor / DECAY_PRECISION) as u64;
+                rate.max((*floor).min(base_rate))
+            }
+        }
+    }
+
+    /// true when no later step can move the rate derived from `base_rate` anymore
+    pub fn is_settled(&self, base_rate: u64, now: u64) -> bool {
+        match self {
+            RateSchedule::Halvings { .. } => self.apply(base_rate, now) == 0,
+            RateSchedule::Linear { amount, floor, .. } => {
+                *amount == 0 || self.apply(base_rate, now) <= (*floor).min(base_rate)
+            }
+            RateSchedule::Exponential { bps, floor, .. } => {
+                *bps == BPS_DENOMINATOR || self.apply(base_rate, now) <= (*floor).min(base_rate)
+            }
+        }
+    }
+
+    /// time of the first scheduled change after `now`
+    pub fn next_change_at(&self, now: u64) -> Option<u64> {
+        match self {
+            RateSchedule::Halvings { at } => at.iter().find(|t| **t > now).copied(),
+            RateSchedule::Linear {
+                start, interval, ..
+            }
+            | RateSchedule::Exponential {
+                start, interval, ..
+            } => {
+                let steps = now.saturating_sub(*start) / interval;
+                start.checked_add((steps + 1).checked_mul(*interval)?)
+            }
+        }
+    }
+}
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RarityMultiplier {
+    pub mining_rate_bps: u64,  //mining rate in bps of the reward token rate
+    pub waiting_time_bps: u64, //mining waiting time in bps of the reward token waiting time
+    pub durability_cost: u64,  //durability lost per claim
+}
+
+impl Default for RarityMultiplier {
+    fn default() -> Self {
+        RarityMultiplier {
+            mining_rate_bps: BPS_DENOMINATOR,
+            waiting_time_bps: BPS_DENOMINATOR,
+            durability_cost: 1,
+        }
+    }
+}
+
+impl RarityMultiplier {
+    pub fn mining_rate(&self, mining_rate: u64) -> u64 {
+        apply_bps(mining_rate, self.mining_rate_bps)
+    }
+
+    pub fn waiting_time(&self, mining_waiting_time: u64) -> u64 {
+        apply_bps(mining_waiting_time, self.waiting_time_bps)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct WithdrawPolicy {
+    pub limit: Option<Uint128>, //amount of the item an address can withdraw within any window, unlimited when None
+    pub window: u64,            //length of the rolling withdrawal window in seconds
+    pub fee_bps: u64, //share of every withdrawal distributed between the stakeholders, below 10000
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct WithdrawUsage {
+    pub withdrawals: Vec<(u64, Uint128)>, //time and amount of the withdrawals still in the window, oldest first
+}
+
+impl WithdrawUsage {
+    /// amount withdrawn within the window
+    pub fn withdrawn(&self) -> Uint128 {
+        self.withdrawals
+            .iter()
+            .fold(Uint128::zero(), |total, (_, amount)| total + *amount)
+    }
+
+    /// adds a withdrawal at `now`, withdrawals of the same block share an entry
+    pub fn record(&mut self, now: u64, amount: Uint128) {
+        match self.withdrawals.last_mut() {
+            Some((withdrawn_at, withdrawn)) if *withdrawn_at == now => *withdrawn += amount,
+            _ => self.withdrawals.push((now, amount)),
+        }
+    }
+}
+
+impl WithdrawPolicy {
+    pub fn is_valid(&self) -> bool {
+        self.fee_bps < BPS_DENOMINATOR && (self.limit.is_none() || self.window > 0)
+    }
+
+    /// usage of the window ending at `now`, withdrawals older than the window are dropped
+    pub fn current_usage(&self, usage: Option<WithdrawUsage>, now: u64) -> WithdrawUsage {
+        let mut usage = usage.unwrap_or_default();
+        usage
+            .withdrawals
+            .retain(|(withdrawn_at, _)| withdrawn_at.saturating_add(self.window) > now);
+        usage
+    }
+
+    /// time the oldest withdrawal leaves the window and frees its amount, None without withdrawals
+    pub fn frees_at(&self, usage: &WithdrawUsage) -> Option<u64> {
+        usage
+            .withdrawals
+            .first()
+            .map(|(withdrawn_at, _)| withdrawn_at.saturating_add(self.window))
+    }
+
+    /// amount left to withdraw in the window, None without a limit
+    pub fn remaining(&self, usage: &WithdrawUsage) -> Option<Uint128> {
+        self.limit
+            .map(|limit| limit.saturating_sub(usage.withdrawn()))
+    }
+}
+
+fn apply_bps(value: u64, bps: u64) -> u64 {
+    let scaled = value as u128 * bps as u128 / BPS_DENOMINATOR as u128;
+    u64::try_from(scaled).unwrap_or(u64::MAX)
+}
+
+/// multiplier of a rarity, rarities without one mine at the reward token values
+pub fn rarity_multiplier(store: &dyn Storage, rarity: &str) -> StdResult<RarityMultiplier> {
+    Ok(RARITY_MULTIPLIERS
+        .may_load(store, rarity.to_string())?
+        .unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionRecipient {
+    Team,
+    Market,
+    Legal,
+    Burn,
+    /// the contract pool that pays out mining rewards
+    Pool,
+    Address(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DistributionShare {
+    pub recipient: DistributionRecipient,
+    pub bps: u64, //share in basis points, all shares of a split sum to 10000
+}
+
+impl DistributionRecipient {
+    /// address receiving the share, named recipients follow the current config
+    pub fn address(&self, config: &Config, env: &Env) -> Addr {
+        match self {
+            DistributionRecipient::Team => Addr::unchecked(&config.team_addr),
+            DistributionRecipient::Market => Addr::unchecked(&config.market_addr),
+            DistributionRecipient::Legal => Addr::unchecked(&config.legal_addr),
+            DistributionRecipient::Burn => Addr::unchecked(&config.burn_addr),
+            DistributionRecipient::Pool => env.contract.address.clone(),
+            DistributionRecipient::Address(addr) => Addr::unchecked(addr),
+        }
+    }
+}
+
+/// split used before the distribution became configurable
+pub fn default_distribution() -> Vec<DistributionShare> {
+    vec![
+        DistributionShare {
+            recipient: DistributionRecipient::Burn,
+            bps: 2500,
+        },
+        DistributionShare {
+            recipient: DistributionRecipient::Team,
+            bps: 1000,
+        },
+        DistributionShare {
+            recipient: DistributionRecipient::Market,
+            bps: 1000,
+        },
+        DistributionShare {
+            recipient: DistributionRecipient::Legal,
+            bps: 500,
+        },
+        DistributionShare {
+            recipient: DistributionRecipient::Pool,
+            bps: 5000,
+        },
+    ]
+}
+
+/// splits `amount` by basis points, the rounding remainder goes to the last share so the
+/// parts always add up to `amount`
+pub fn split_amount(shares: &[DistributionShare], amount: Uint128) -> Vec<Uint128> {
+    let mut parts: Vec<Uint128> = shares
+        .iter()
+        .map(|share| amount.multiply_ratio(share.bps, BPS_DENOMINATOR))
+        .collect();
+    let distributed = parts.iter().fold(Uint128::zero(), |sum, part| sum + *part);
+    if let Some(last) = parts.last_mut() {
+        *last += amount - distributed;
+    }
+    parts
 }
 
 // distributing amount between stakeholders
@@ -118,70 +657,87 @@ pub fn distribute_amount(
     amount: Uint128,
     config: &Config,
     env: &Env,
-) {
+) -> StdResult<()> {
     if amount == Uint128::zero() {
-        return;
-    }
-    // burn user amount 25%
-    let burn_amount = amount.multiply_ratio(Uint128::from(25u128), Uint128::from(100u128));
-    // transferring amount of team 10% and market 10%
-    let team_market_amount = amount.multiply_ratio(Uint128::from(10u128), Uint128::from(100u128));
-    // transferring amount of legalization process 5%
-    let legal_amount = amount.multiply_ratio(Uint128::from(5u128), Uint128::from(100u128));
-    // transferring amount of contract pool 50%
-    let contract_pool_amount = amount - burn_amount - team_market_amount - team_market_amount;
-    //assigning amount to legal address
-    add_amount_in_item_address(
-        store,
-        config.legal_addr.to_string(),
-        item_name.to_string(),
-        legal_amount,
-    );
-    //assigning amount to team address
-    add_amount_in_item_address(
-        store,
-        config.team_addr.to_string(),
-        item_name.to_string(),
-        team_market_amount,
-    );
-    //assigning amount to marketing address
-    add_amount_in_item_address(
-        store,
-        config.market_addr.to_string(),
-        item_name.to_string(),
-        team_market_amount,
-    );
-    //assigning amount to contract address
-    add_amount_in_item_address(
-        store,
-        env.contract.address.to_string(),
-        item_name.to_string(),
-        contract_pool_amount,
-    );
-    //assigning amount to burn address
-    add_amount_in_item_address(store, config.burn_addr.to_string(), item_name, burn_amount);
+        return Ok(());
+    }
+    let shares = if let Some(shares) = ITEM_DISTRIBUTION.may_load(store, item_name.to_string())? {
+        shares
+    } else {
+        DISTRIBUTION.load(store)?
+    };
+    for (share, part) in shares.iter().zip(split_amount(&shares, amount)) {
+        // the burn share waits for FlushBurns outside of the balances anyone can spend
+        if matches!(share.recipient, DistributionRecipient::Burn) {
+            let pending = PENDING_BURNS
+                .may_load(store, &item_name)?
+                .unwrap_or_default();
+            PENDING_BURNS.save(store, &item_name, &(pending + part))?;
+            continue;
+        }
+        add_amount_in_item_address(
+            store,
+            &share.recipient.address(config, env),
+            &item_name,
+            part,
+        )?;
+    }
+    Ok(())
+}
+
+/// energy of a user including lazily accrued regeneration, and the time it is accounted up to
+pub fn load_energy(
+    store: &dyn Storage,
+    energy_config: &EnergyConfig,
+    user: &str,
+    now: u64,
+) -> StdResult<(Uint128, u64)> {
+    let energy = USER_ENERGY_LEVEL
+        .may_load(store, user.to_string())?
+        .unwrap_or_default();
+    let updated_at = USER_ENERGY_UPDATED_AT
+        .may_load(store, user.to_string())?
+        .unwrap_or(now);
+    Ok(energy_config.regenerate(energy, updated_at, now))
+}
+
+pub fn save_energy(
+    store: &mut dyn Storage,
+    user: &str,
+    energy: Uint128,
+    accounted_at: u64,
+) -> StdResult<()> {
+    USER_ENERGY_LEVEL.save(store, user.to_string(), &energy)?;
+    USER_ENERGY_UPDATED_AT.save(store, user.to_string(), &accounted_at)
 }
 
 pub fn add_amount_in_item_address(
     store: &mut dyn Storage,
-    addr: String,
-    item: String,
+    addr: &Addr,
+    item: &str,
     amount: Uint128,
-) {
-    let mut item_key = addr;
-    item_key.push_str(&item);
-    let mut item_amount = if let Some(item_amount) = USER_ITEM_AMOUNT
-        .may_load(store, item_key.to_string())
-        .unwrap()
-    {
-        item_amount
-    } else {
-        Uint128::zero()
-    };
+) -> StdResult<()> {
+    let mut item_amount = USER_ITEM_AMOUNT
+        .may_load(store, (addr, item))?
+        .unwrap_or_default();
     item_amount += amount;
-    USER_ITEM_AMOUNT
-        .save(store, item_key.to_string(), &item_amount)
-        .unwrap();
+    save_item_amount(store, addr, item, item_amount)
+}
+
+/// sets the balance of an address and keeps the supply of the item in step, every balance
+/// change goes through here so the supply always equals the sum of all balances
+pub fn save_item_amount(
+    store: &mut dyn Storage,
+    addr: &Addr,
+    item: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let previous = USER_ITEM_AMOUNT
+        .may_load(store, (addr, item))?
+        .unwrap_or_default();
+    let supply = ITEM_SUPPLY.may_load(store, item)?.unwrap_or_default();
+    ITEM_SUPPLY.save(store, item, &(supply.saturating_sub(previous) + amount))?;
+    USER_ITEM_AMOUNT.save(store, (addr, item), &amount)
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -191,25 +747,89 @@ pub struct ToolTemplate {
     pub image: String,
     pub rarity: String,
     pub durability: u64,
-    pub required_amount: Vec<Uint128>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_amount: Vec<Uint128>, //legacy positional cost, moved into required_items on migrate
+    #[serde(default)]
+    pub required_items: Vec<(String, Uint128)>, //item name and amount paid to mint the tool
     pub token_uri: Option<String>,
 }
 
+pub const DEFAULT_ITEM_DECIMALS: u8 = 6;
+
+/// item order of the positional costs templates had before costs named their items
+pub const LEGACY_TEMPLATE_ITEMS: [&str; 4] = ["gWood", "gFood", "gGold", "gStone"];
+
+/// names of every registered item, the game dev tokens
+pub fn registered_items(store: &dyn Storage) -> StdResult<Vec<String>> {
+    ITEMS
+        .keys(store, None, None, Order::Ascending)
+        .map(|k| String::from_utf8(k).map_err(StdError::invalid_utf8))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemInfo {
+    pub token_addr: String, //cw20 contract of the item, or its bank denom for native items
+    pub decimals: u8,
+    pub enabled: bool, //disabled items can not be deposited or used in new template costs
+    #[serde(default)]
+    pub native: bool, //deposited and withdrawn as bank coins instead of cw20 tokens
+}
 
 pub const RARITY_TYPES: Map<String, String> = Map::new("Rarities"); // contains rarity stages for upgradation
 pub const CONFIG: Item<Config> = Item::new("Config");
+pub const ENERGY_CONFIG: Item<EnergyConfig> = Item::new("EnergyConfig"); // energy economy parameters editable by admin
+pub const EMISSION_CONFIG: Item<EmissionConfig> = Item::new("EmissionConfig"); // per epoch caps on mined items
+pub const EMISSION_USAGE: Item<EmissionUsage> = Item::new("EmissionUsage"); // amounts mined in the latest epoch
+pub const DISTRIBUTION: Item<Vec<DistributionShare>> = Item::new("Distribution"); // default split of spent items between stakeholders
+pub const ITEM_DISTRIBUTION: Map<String, Vec<DistributionShare>> = Map::new("ItemDistribution"); // split overrides keyed by item name
+pub const BURNED_ITEMS: Map<String, Uint128> = Map::new("BurnedItems"); // cumulative amount burned on chain keyed by item name
+pub const PENDING_BURNS: Map<&str, Uint128> = Map::new("PendingBurns"); // burn shares waiting for FlushBurns keyed by item name
+pub const STAKED_TOOL_COUNT: Map<(&str, &str), u64> = Map::new("StakedTemplateCount"); // number of staked tools keyed by (tool type, rarity)
+pub const RARITY_MULTIPLIERS: Map<String, RarityMultiplier> = Map::new("RarityMultipliers"); // reward multipliers keyed by rarity
+pub const WITHDRAW_POLICIES: Map<String, WithdrawPolicy> = Map::new("WithdrawPolicies"); // withdrawal limits and fees keyed by item name
+pub const USER_WITHDRAWALS: Map<(&Addr, &str), WithdrawUsage> = Map::new("UserWithdrawals"); // withdrawals in the rolling window keyed by (address, item name)
+pub const RESERVE_SWEEP_COUNT: Item<u64> = Item::new("ReserveSweepCount"); // number of reserve sweeps done so far
+pub const RESERVE_SWEEPS: Map<U64Key, ReserveSweep> = Map::new("ReserveSweeps"); // reserve sweep history keyed by sweep id
 pub const TOOL_SET_MAP: Map<String, Vec<String>> = Map::new("ToolSet"); // contains tool set section wise e.g. (wood miner -> Axe, Saw e.t.c)
 pub const USER_STAKED_INFO: Map<String, HashSet<String>> = Map::new("UserStakedInfo"); // contains user nft staked info
-pub const REWARD_TOKEN: Map<String, RewardToken> = Map::new("RewardToken"); //contains reward tokens
+pub const REWARD_TOKEN: Map<(&str, &str), RewardToken> = Map::new("TemplateRewardToken"); //contains reward tokens keyed by (tool type, rarity)
 pub const TOOL_TYPE_NAMES: Item<Vec<String>> = Item::new("ToolTypeNames"); // contains tool type names
 pub const USER_ENERGY_LEVEL: Map<String, Uint128> = Map::new("UserEnergyLevel"); //to contain the user energy for claiming reward
-pub const USER_ITEM_AMOUNT: Map<String, Uint128> = Map::new("UserItemAmount"); // contains the amount of items assigned to particular address
-pub const ITEM_TOKEN_MAPPING: Map<String, String> = Map::new("ItemTokenMapping"); //key will be address and value will be item name
+pub const USER_ENERGY_UPDATED_AT: Map<String, u64> = Map::new("UserEnergyUpdatedAt"); //time up to which the user energy includes regeneration
+pub const USER_ITEM_AMOUNT: Map<(&Addr, &str), Uint128> = Map::new("UserItemBalances"); // contains the amount of items assigned to particular address, keyed by (address, item name)
+pub const ITEM_SUPPLY: Map<&str, Uint128> = Map::new("ItemSupply"); // sum of the balances of all addresses keyed by item name
+pub const ITEMS: Map<String, ItemInfo> = Map::new("ItemRegistry"); // registered game items keyed by item name
+pub const LEGACY_ITEM_TOKEN_MAPPING: Map<String, String> = Map::new("ItemTokenMapping"); // item name to cw20 address before the registry, moved into ITEMS on migrate
 pub const TOKEN_ITEM_MAPPING: Map<String, String> = Map::new("TokenItemMapping"); //key will be item name and value will be address
 pub const LAST_GEN_TOKEN_ID: Item<u64> = Item::new("LastGenTokenId"); //contains the last token id in generating of nft
 pub const TOOL_TEMPLATE_MAP: Map<String, ToolTemplate> = Map::new("ToolTemplateMap"); //contains the template of tool or snapshot to create the new one
-pub const GAME_DEV_TOKENS_NAME: Item<Vec<String>> = Item::new("GameDevTokensName"); // contains the name of game dev token e.g. gWood, gGold e.t.c
+pub const LEGACY_GAME_DEV_TOKENS_NAME: Item<Vec<String>> = Item::new("GameDevTokensName"); // game dev token names before the item registry, removed on migrate
 pub const TOOL_PACK_SET: Map<String, Vec<String>> = Map::new("ToolPackSet"); //contains pack set against tool type
-pub const USER_REPAIR_KITS: Map<String, String> = Map::new("UserRepairKits");
+pub const USER_REPAIR_KITS: Map<(&Addr, &str), String> = Map::new("UserToolRepairKits"); // contains the deployed repair kit token id, keyed by (address, tool type)
 pub const REPAIR_KIT_SET: Map<String, Vec<String>> = Map::new("RepairKitSet");
-pub const REPAIRING_FEE: Map<String, Uint128> = Map::new("RepairingFee");
\ No newline at end of file
+pub const REPAIRING_FEE: Map<String, Uint128> = Map::new("RepairingFee");
+
+/// legacy balances keyed by address + item name, only read while migrating
+pub const LEGACY_USER_ITEM_AMOUNT: Map<String, Uint128> = Map::new("UserItemAmount");
+/// legacy repair kits keyed by address + "RepKit" + tool type, only read while migrating
+pub const LEGACY_USER_REPAIR_KITS: Map<String, String> = Map::new("UserRepairKits");
+/// legacy reward tokens keyed by tool name, only read while migrating
+pub const LEGACY_REWARD_TOKEN: Map<String, RewardToken> = Map::new("RewardToken");
+
+/// splits a raw (String, String) key returned by ranging over a whole map
+pub fn parse_pair_key(key: &[u8]) -> StdResult<(String, String)> {
+    // all but the last key part are prefixed with their length as a big endian u16
+    if key.len() < 2 {
+        return Err(StdError::generic_err("Invalid pair key"));
+    }
+    let first_len = u16::from_be_bytes([key[0], key[1]]) as usize;
+    if key.len() < 2 + first_len {
+        return Err(StdError::generic_err("Invalid pair key"));
+    }
+    let first =
+        String::from_utf8(key[2..2 + first_len].to_vec()).map_err(StdError::invalid_utf8)?;
+    let second =
+        String::from_utf8(key[2 + first_len..].to_vec()).map_err(StdError::invalid_utf8)?;
+    Ok((first, second))
+}
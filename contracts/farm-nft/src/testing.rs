@@ -1,10 +1,31 @@
-use crate::contract::{execute, instantiate};
+use crate::contract::{execute, instantiate, migrate, query};
+use crate::error::ContractError;
 use crate::mock::mock_dependencies;
-use crate::msg::{Cw721HookMsg, ExecuteMsg, InstantiateMsg, MintMsg, ToolTemplateMsg};
+use crate::msg::{
+    BurnedItemsResponse, Cw721HookMsg, DistributionResponse, ExecuteMsg, InstantiateMsg,
+    ItemAmount, ItemDistributionInfo, ItemTokenInfo, ItemTokensResponse, MigrateMsg, MintMsg,
+    NftInfoResponse, PoolStatusResponse, QueryMsg, ReserveSweepsResponse, RewardTokensResponse,
+    StakeholderBalance, StakeholderBalancesResponse, ToolTemplateMsg, UpdateConfigMsg,
+    UpdateEmissionConfigMsg, UpdateEnergyConfigMsg, UserEnergyInfoResponse,
+    WithdrawAllowanceResponse,
+};
+use crate::state::{
+    add_amount_in_item_address, default_distribution, distribute_amount, load_emission_usage,
+    split_amount, tokens, ClaimEnergyCost, Config, DistributionRecipient, DistributionShare,
+    EmissionConfig, EnergyConfig, ItemEmissionCap, RarityMultiplier, RateSchedule, RewardOutput,
+    RewardToken, WithdrawPolicy, BPS_DENOMINATOR, CONFIG, EMISSION_USAGE, ITEM_SUPPLY,
+    LEGACY_REWARD_TOKEN, LEGACY_USER_ITEM_AMOUNT, LEGACY_USER_REPAIR_KITS, PENDING_BURNS,
+    REPAIRING_FEE, REWARD_TOKEN, USER_ITEM_AMOUNT, USER_REPAIR_KITS,
+};
 use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::Coin;
-use cosmwasm_std::{to_binary, Uint128};
-use cw721::Cw721ReceiveMsg;
+use cosmwasm_std::{coins, from_binary, to_binary, Addr, BankMsg, CosmosMsg, Uint128, WasmMsg};
+use cosmwasm_std::{Coin, DepsMut, MessageInfo, Order};
+use cw20::Cw20ExecuteMsg;
+use cw721::{
+    ApprovalResponse, ApprovalsResponse, Cw721ReceiveMsg, Expiration, OperatorsResponse,
+    OwnerOfResponse, TokensResponse,
+};
+use cw721_base::msg::MinterResponse;
 
 mod tests {
     use super::*;
@@ -84,6 +105,7 @@ mod tests {
         let add_stakeable_token_msg = ExecuteMsg::AddItemToken {
             item_token_addr: "woodaddr".to_string(),
             item_name: "gWood".to_string(),
+            decimals: None,
         };
 
         let _res = execute(
@@ -97,6 +119,7 @@ mod tests {
         let add_stakeable_token_msg = ExecuteMsg::AddItemToken {
             item_token_addr: "foodaddr".to_string(),
             item_name: "gFood".to_string(),
+            decimals: None,
         };
 
         let _res = execute(
@@ -110,6 +133,7 @@ mod tests {
         let add_stakeable_token_msg = ExecuteMsg::AddItemToken {
             item_token_addr: "Goldaddr".to_string(),
             item_name: "gGold".to_string(),
+            decimals: None,
         };
 
         let _res = execute(
@@ -123,6 +147,7 @@ mod tests {
         let add_stakeable_token_msg = ExecuteMsg::AddItemToken {
             item_token_addr: "Stoneaddr".to_string(),
             item_name: "gStone".to_string(),
+            decimals: None,
         };
 
         let _res = execute(
@@ -133,6 +158,74 @@ mod tests {
         )
         .unwrap();
 
+        let add_distribution_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 100u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            add_distribution_msg,
+        )
+        .unwrap();
+
+        let add_distribution_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gStone".to_string(),
+            tool_type: "Stone Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 100u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            add_distribution_msg,
+        )
+        .unwrap();
+
+        let add_distribution_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gGold".to_string(),
+            tool_type: "Gold Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 100u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            add_distribution_msg,
+        )
+        .unwrap();
+
+        let add_distribution_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gFood".to_string(),
+            tool_type: "Food Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 100u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            add_distribution_msg,
+        )
+        .unwrap();
+
         let add_stakeable_token_msg = ExecuteMsg::AddToolTemplate({
             ToolTemplateMsg {
                 tool_type: "Wood Miner".to_string(),
@@ -140,11 +233,9 @@ mod tests {
                 description: "".to_string(),
                 image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
                 rarity: "Pack".to_string(),
-                required_gwood_amount: Uint128::zero(),
-                required_gfood_amount: Uint128::zero(),
-                required_ggold_amount: Uint128::zero(),
-                required_gstone_amount: Uint128::zero(),
+                required_items: vec![],
                 durability: 10,
+                token_uri: None,
             }
         });
 
@@ -163,11 +254,9 @@ mod tests {
                 description: "".to_string(),
                 image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
                 rarity: "Common".to_string(),
-                required_gwood_amount: Uint128::zero(),
-                required_gfood_amount: Uint128::zero(),
-                required_ggold_amount: Uint128::zero(),
-                required_gstone_amount: Uint128::zero(),
+                required_items: vec![],
                 durability: 10,
+                token_uri: None,
             }
         });
 
@@ -186,11 +275,9 @@ mod tests {
                 description: "".to_string(),
                 image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
                 rarity: "Common".to_string(),
-                required_gwood_amount: Uint128::zero(),
-                required_gfood_amount: Uint128::zero(),
-                required_ggold_amount: Uint128::zero(),
-                required_gstone_amount: Uint128::zero(),
+                required_items: vec![],
                 durability: 10,
+                token_uri: None,
             }
         });
 
@@ -209,11 +296,9 @@ mod tests {
                 description: "".to_string(),
                 image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
                 rarity: "Common".to_string(),
-                required_gwood_amount: Uint128::zero(),
-                required_gfood_amount: Uint128::zero(),
-                required_ggold_amount: Uint128::zero(),
-                required_gstone_amount: Uint128::zero(),
+                required_items: vec![],
                 durability: 10,
+                token_uri: None,
             }
         });
 
@@ -232,11 +317,9 @@ mod tests {
                 description: "".to_string(),
                 image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
                 rarity: "Common".to_string(),
-                required_gwood_amount: Uint128::zero(),
-                required_gfood_amount: Uint128::zero(),
-                required_ggold_amount: Uint128::zero(),
-                required_gstone_amount: Uint128::zero(),
+                required_items: vec![],
                 durability: 10,
+                token_uri: None,
             }
         });
 
@@ -255,11 +338,9 @@ mod tests {
                 description: "".to_string(),
                 image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
                 rarity: "Common".to_string(),
-                required_gwood_amount: Uint128::zero(),
-                required_gfood_amount: Uint128::zero(),
-                required_ggold_amount: Uint128::zero(),
-                required_gstone_amount: Uint128::zero(),
+                required_items: vec![],
                 durability: 10,
+                token_uri: None,
             }
         });
 
@@ -271,62 +352,6 @@ mod tests {
         )
         .unwrap();
 
-        let add_distribution_msg = ExecuteMsg::AddRewardToken {
-            item_name: "gWood".to_string(),
-            tool_name: "Axe".to_string(),
-            mining_rate: 100u64,
-            mining_waiting_time: 100u64,
-        };
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            minter.clone(),
-            add_distribution_msg,
-        )
-        .unwrap();
-
-        let add_distribution_msg = ExecuteMsg::AddRewardToken {
-            item_name: "gFood".to_string(),
-            tool_name: "Chisel".to_string(),
-            mining_rate: 100u64,
-            mining_waiting_time: 100u64,
-        };
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            minter.clone(),
-            add_distribution_msg,
-        )
-        .unwrap();
-
-        let add_distribution_msg = ExecuteMsg::AddRewardToken {
-            item_name: "gGold".to_string(),
-            tool_name: "Hammer".to_string(),
-            mining_rate: 100u64,
-            mining_waiting_time: 100u64,
-        };
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            minter.clone(),
-            add_distribution_msg,
-        )
-        .unwrap();
-
-        let add_distribution_msg = ExecuteMsg::AddRewardToken {
-            item_name: "gStone".to_string(),
-            tool_name: "Fish Net".to_string(),
-            mining_rate: 100u64,
-            mining_waiting_time: 100u64,
-        };
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            minter.clone(),
-            add_distribution_msg,
-        )
-        .unwrap();
-
         let add_distribution_msg = ExecuteMsg::Mint(MintMsg {
             name: "Wood Miner Pack".to_string(),
             tool_type: "Wood Miner".to_string(),
@@ -425,6 +450,2363 @@ mod tests {
         )
         .unwrap();
     }
-}
 
-// cargo test -- --show-output
+    fn setup_contract(deps: DepsMut) -> MessageInfo {
+        let init_msg = InstantiateMsg {
+            name: "Miners World".to_string(),
+            symbol: "*****".to_string(),
+            burn_addr: "burn_address".to_string(),
+            team_addr: "team_address".to_string(),
+            market_addr: "market_address".to_string(),
+            legal_addr: "legal_address".to_string(),
+            stake_limit: 20,
+            durability_from_start_time: 2592000,
+            reserve_addr: "reserve_address".to_string(),
+            repair_kit_waiting_time: 360u64,
+        };
+        let minter = mock_info(&"minter".to_string(), &[]);
+        instantiate(deps, mock_env(), minter.clone(), init_msg).unwrap();
+        minter
+    }
+
+    fn mint_axe(deps: DepsMut, minter: &MessageInfo, owner: &str) -> String {
+        let mut deps = deps;
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 100u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(deps.branch(), mock_env(), minter.clone(), reward_msg).unwrap();
+        let template_msg = ExecuteMsg::AddToolTemplate(ToolTemplateMsg {
+            tool_type: "Wood Miner".to_string(),
+            name: "Axe".to_string(),
+            description: "".to_string(),
+            image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
+            rarity: "Common".to_string(),
+            required_items: vec![],
+            durability: 10,
+            token_uri: None,
+        });
+        execute(deps.branch(), mock_env(), minter.clone(), template_msg).unwrap();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            name: "Axe".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            pre_mint_tool: None,
+            owner: Addr::unchecked(owner),
+            rarity: "Common".to_string(),
+            minting_count: None,
+        });
+        let res = execute(deps, mock_env(), minter.clone(), mint_msg).unwrap();
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "token_id")
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    /// sends the token to the contract with the Stake hook and delivers the hook
+    fn stake_tool(mut deps: DepsMut, owner: &str, token_id: &str) {
+        let contract_addr = mock_env().contract.address.to_string();
+        let hook = to_binary(&Cw721HookMsg::Stake {}).unwrap();
+        let send_msg = ExecuteMsg::SendNft {
+            contract: contract_addr.clone(),
+            token_id: token_id.to_string(),
+            msg: hook.clone(),
+        };
+        execute(deps.branch(), mock_env(), mock_info(owner, &[]), send_msg).unwrap();
+        let hook_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: owner.to_string(),
+            token_id: token_id.to_string(),
+            msg: hook,
+        });
+        execute(deps, mock_env(), mock_info(&contract_addr, &[]), hook_msg).unwrap();
+    }
+
+    #[test]
+    fn test_unauthorized_mint() {
+        let mut deps = mock_dependencies(&[]);
+        setup_contract(deps.as_mut());
+        let user = mock_info(&"user1".to_string(), &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            name: "Axe".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            pre_mint_tool: None,
+            owner: user.sender.clone(),
+            rarity: "Common".to_string(),
+            minting_count: None,
+        });
+        let err = execute(deps.as_mut(), mock_env(), user, mint_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn test_mint_errors_are_recoverable() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let user = mock_info(&"user1".to_string(), &[]);
+
+        let mint_msg = MintMsg {
+            name: "Axe".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            pre_mint_tool: None,
+            owner: user.sender,
+            rarity: "Common".to_string(),
+            minting_count: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::BatchMint(mint_msg.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::MintingCountMissing {});
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            ExecuteMsg::Mint(mint_msg),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ToolTemplateNotFound {
+                template: "Wood MinerCommon".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_approve_all_operator_can_transfer() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let owner = mock_info("owner", &[]);
+        let operator = mock_info("operator", &[]);
+
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "operator".to_string(),
+            token_id: token_id.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            operator.clone(),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let approve_msg = ExecuteMsg::ApproveAll {
+            operator: "operator".to_string(),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner, approve_msg).unwrap();
+
+        let res: OperatorsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllOperators {
+                    owner: "owner".to_string(),
+                    include_expired: None,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.operators.len(), 1);
+        assert_eq!(res.operators[0].spender, "operator");
+
+        execute(deps.as_mut(), mock_env(), operator, transfer_msg).unwrap();
+        let res: OwnerOfResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::OwnerOf {
+                    token_id,
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.owner, "operator");
+    }
+
+    #[test]
+    fn test_migrate_rewrites_legacy_item_keys() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_item_msg = ExecuteMsg::AddItemToken {
+            item_token_addr: "woodaddr".to_string(),
+            item_name: "gWood".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter, add_item_msg).unwrap();
+
+        LEGACY_USER_ITEM_AMOUNT
+            .save(
+                deps.as_mut().storage,
+                "user1gWood".to_string(),
+                &Uint128::from(50u128),
+            )
+            .unwrap();
+        // bare item key written by the old reserve sweep
+        LEGACY_USER_ITEM_AMOUNT
+            .save(deps.as_mut().storage, "gWood".to_string(), &Uint128::zero())
+            .unwrap();
+        // balance written before item supplies were kept
+        USER_ITEM_AMOUNT
+            .save(
+                deps.as_mut().storage,
+                (&Addr::unchecked("user2"), "gWood"),
+                &Uint128::from(30u128),
+            )
+            .unwrap();
+        LEGACY_USER_REPAIR_KITS
+            .save(
+                deps.as_mut().storage,
+                "user1RepKitWood Miner".to_string(),
+                &"7".to_string(),
+            )
+            .unwrap();
+
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                legacy_template_items: None,
+            },
+        )
+        .unwrap();
+
+        let balance: Uint128 = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::UserItemBalance {
+                    user_address: "user1".to_string(),
+                    item_name: "gWood".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(balance, Uint128::from(50u128));
+        let user = Addr::unchecked("user1");
+        assert_eq!(
+            USER_REPAIR_KITS
+                .load(deps.as_ref().storage, (&user, "Wood Miner"))
+                .unwrap(),
+            "7"
+        );
+        assert!(LEGACY_USER_ITEM_AMOUNT
+            .may_load(deps.as_ref().storage, "gWood".to_string())
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            ITEM_SUPPLY.load(deps.as_ref().storage, "gWood").unwrap(),
+            Uint128::from(80u128)
+        );
+    }
+
+    #[test]
+    fn test_staked_token_is_locked_until_unstake() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let contract = mock_info(mock_env().contract.address.as_str(), &[]);
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 10,
+            mining_waiting_time: 0,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter, reward_msg).unwrap();
+
+        // SendNft moves custody to the contract, the hook is then delivered by the contract
+        let send_msg = ExecuteMsg::SendNft {
+            contract: mock_env().contract.address.to_string(),
+            token_id: token_id.clone(),
+            msg: to_binary(&Cw721HookMsg::Stake {}).unwrap(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), send_msg).unwrap();
+        let hook_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "owner".to_string(),
+            token_id: token_id.clone(),
+            msg: to_binary(&Cw721HookMsg::Stake {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), contract.clone(), hook_msg).unwrap();
+
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "thief".to_string(),
+            token_id: token_id.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), contract.clone(), transfer_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokenStaked {
+                token_id: token_id.clone()
+            }
+        );
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), contract, burn_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokenStaked {
+                token_id: token_id.clone()
+            }
+        );
+
+        let unstake_msg = ExecuteMsg::Unstake {
+            token_id: token_id.clone(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            unstake_msg,
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let res: OwnerOfResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::OwnerOf {
+                    token_id,
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.owner, "owner");
+    }
+
+    #[test]
+    fn test_stake_many_and_unstake_many() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let first_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let second_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let contract_addr = mock_env().contract.address.to_string();
+        let owner = mock_info("owner", &[]);
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 10,
+            mining_waiting_time: 0,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter, reward_msg).unwrap();
+
+        // a single approval lets the hook pull the second token
+        let approve_msg = ExecuteMsg::ApproveAll {
+            operator: contract_addr.clone(),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg).unwrap();
+        let hook = to_binary(&Cw721HookMsg::StakeMany {
+            token_ids: vec![second_id.clone()],
+        })
+        .unwrap();
+        let send_msg = ExecuteMsg::SendNft {
+            contract: contract_addr.clone(),
+            token_id: first_id.clone(),
+            msg: hook.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), send_msg).unwrap();
+        let hook_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "owner".to_string(),
+            token_id: first_id.clone(),
+            msg: hook,
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&contract_addr, &[]),
+            hook_msg,
+        )
+        .unwrap();
+
+        let unstake_msg = ExecuteMsg::UnstakeMany {
+            token_ids: vec![first_id.clone(), second_id.clone(), "999".to_string()],
+        };
+        let res = execute(deps.as_mut(), mock_env(), owner, unstake_msg).unwrap();
+        let result = |key: String| {
+            res.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(result("unstaked".to_string()), "2");
+        assert_eq!(result(format!("token_{}", first_id)), "unstaked");
+        assert_eq!(result(format!("token_{}", second_id)), "unstaked");
+        assert_eq!(
+            result("token_999".to_string()),
+            ContractError::TokenNotStaked {
+                token_id: "999".to_string()
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_energy_config_controls_starter_energy() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "owner");
+
+        let update_msg = ExecuteMsg::UpdateEnergyConfig(UpdateEnergyConfigMsg {
+            starter_energy: Some(Uint128::from(50u128)),
+            claim_cost: None,
+            claim_cost_overrides: Some(vec![ClaimEnergyCost {
+                tool_type: Some("Wood Miner".to_string()),
+                rarity: None,
+                cost: Uint128::from(5u128),
+            }]),
+            refill_item: None,
+            refill_ratio: None,
+            max_energy: Some(Uint128::from(40u128)),
+            regen_amount: None,
+            regen_interval: None,
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            update_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), minter, update_msg).unwrap();
+
+        let energy_config: EnergyConfig =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::EnergyConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(energy_config.max_energy, Some(Uint128::from(40u128)));
+        assert_eq!(
+            energy_config.claim_cost("Wood Miner", "Common"),
+            Uint128::from(5u128)
+        );
+        assert_eq!(
+            energy_config.claim_cost("Gold Miner", "Common"),
+            Uint128::from(3u128)
+        );
+
+        stake_tool(deps.as_mut(), "owner", &token_id);
+
+        // the starter grant is capped at max_energy
+        let energy: UserEnergyInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::UserEnergyInfo {
+                    user_address: "owner".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(energy.energy, Uint128::from(40u128));
+        assert_eq!(energy.next_full_at, None);
+    }
+
+    #[test]
+    fn test_energy_regenerates_up_to_max() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let update_msg = ExecuteMsg::UpdateEnergyConfig(UpdateEnergyConfigMsg {
+            starter_energy: Some(Uint128::from(10u128)),
+            claim_cost: None,
+            claim_cost_overrides: None,
+            refill_item: None,
+            refill_ratio: None,
+            max_energy: Some(Uint128::from(40u128)),
+            regen_amount: Some(Uint128::from(5u128)),
+            regen_interval: Some(60),
+        });
+        execute(deps.as_mut(), mock_env(), minter, update_msg).unwrap();
+        stake_tool(deps.as_mut(), "owner", &token_id);
+
+        let staked_at = mock_env().block.time.seconds();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let energy: UserEnergyInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UserEnergyInfo {
+                    user_address: "owner".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        // two full intervals passed, the partial one is kept for the next step
+        assert_eq!(energy.energy, Uint128::from(20u128));
+        assert_eq!(energy.max_energy, Some(Uint128::from(40u128)));
+        assert_eq!(energy.next_full_at, Some(staked_at + 360));
+
+        env.block.time = env.block.time.plus_seconds(10_000);
+        let energy: UserEnergyInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::UserEnergyInfo {
+                    user_address: "owner".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(energy.energy, Uint128::from(40u128));
+
+        // a full time past u64::MAX is reported as never instead of overflowing
+        let slow_regen = EnergyConfig {
+            max_energy: Some(Uint128::from(u128::MAX)),
+            regen_amount: Uint128::from(1u128),
+            regen_interval: u64::MAX,
+            ..EnergyConfig::default()
+        };
+        assert_eq!(slow_regen.full_at(Uint128::zero(), 1000), None);
+        assert_eq!(
+            slow_regen.full_at(Uint128::from(u128::MAX), 1000),
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn test_distribution_must_sum_to_full_amount() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let shares = vec![
+            DistributionShare {
+                recipient: DistributionRecipient::Team,
+                bps: 3333,
+            },
+            DistributionShare {
+                recipient: DistributionRecipient::Pool,
+                bps: 6000,
+            },
+        ];
+        let set_msg = ExecuteMsg::SetDistribution {
+            item_name: Some("gWood".to_string()),
+            shares: shares.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), set_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidDistribution { total_bps: 9333 });
+
+        let mut shares = shares;
+        shares[1].bps = 6667;
+        let set_msg = ExecuteMsg::SetDistribution {
+            item_name: Some("gWood".to_string()),
+            shares: shares.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), minter, set_msg).unwrap();
+        let res: DistributionResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Distribution {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.shares, default_distribution());
+        assert_eq!(res.item_overrides[0].item_name, "gWood");
+
+        // rounding dust stays inside the split
+        let parts = split_amount(&shares, Uint128::from(7u128));
+        assert_eq!(parts, vec![Uint128::from(2u128), Uint128::from(5u128)]);
+        let parts = split_amount(&default_distribution(), Uint128::from(7u128));
+        let total = parts.iter().fold(Uint128::zero(), |sum, part| sum + *part);
+        assert_eq!(total, Uint128::from(7u128));
+    }
+
+    #[test]
+    fn test_flush_burns_burns_accrued_items() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_token_msg = ExecuteMsg::AddItemToken {
+            item_token_addr: "woodaddr".to_string(),
+            item_name: "gWood".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), add_token_msg).unwrap();
+        // the team address burns too, its payout must not include the burn share
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            team_addr: None,
+            market_addr: None,
+            legal_addr: None,
+            burn_addr: Some("team_address".to_string()),
+            stake_limit: None,
+            durability_from_start_time: None,
+            reserve_addr: None,
+            pool_floor: None,
+            partial_claims: None,
+            transfer_fee_bps: None,
+        });
+        execute(deps.as_mut(), mock_env(), minter.clone(), update_msg).unwrap();
+        let config = CONFIG.load(&deps.storage).unwrap();
+        distribute_amount(
+            &mut deps.storage,
+            "gWood".to_string(),
+            Uint128::from(160u128),
+            &config,
+            &mock_env(),
+        )
+        .unwrap();
+        let payout_msg = ExecuteMsg::PayoutStakeholders {
+            items: Some(vec!["gWood".to_string()]),
+        };
+        let res = execute(deps.as_mut(), mock_env(), minter, payout_msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "woodaddr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "team_address".to_string(),
+                    amount: Uint128::from(16u128)
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let anyone = mock_info("anyone", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            anyone.clone(),
+            ExecuteMsg::FlushBurns {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "woodaddr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(40u128)
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // nothing left to burn on a second flush
+        let res = execute(deps.as_mut(), mock_env(), anyone, ExecuteMsg::FlushBurns {}).unwrap();
+        assert!(res.messages.is_empty());
+        let res: BurnedItemsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BurnedItems {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.items[0].item_name, "gWood");
+        assert_eq!(res.items[0].amount, Uint128::from(40u128));
+    }
+
+    #[test]
+    fn test_reserve_sweep_keeps_pool_floor() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_token_msg = ExecuteMsg::AddItemToken {
+            item_token_addr: "woodaddr".to_string(),
+            item_name: "gWood".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), add_token_msg).unwrap();
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            team_addr: None,
+            market_addr: None,
+            legal_addr: None,
+            burn_addr: None,
+            stake_limit: None,
+            durability_from_start_time: None,
+            reserve_addr: None,
+            pool_floor: Some(Uint128::from(30u128)),
+            partial_claims: None,
+            transfer_fee_bps: None,
+        });
+        execute(deps.as_mut(), mock_env(), minter.clone(), update_msg).unwrap();
+        let pool = mock_env().contract.address;
+        add_amount_in_item_address(&mut deps.storage, &pool, "gWood", Uint128::from(100u128))
+            .unwrap();
+
+        let sweep_msg = ExecuteMsg::TransferReserveAmount {
+            items: Some(vec!["gWood".to_string()]),
+            amount: Some(Uint128::from(80u128)),
+        };
+        // the amount is capped at what is above the floor
+        let res = execute(deps.as_mut(), mock_env(), minter.clone(), sweep_msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "woodaddr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "reserve_address".to_string(),
+                    amount: Uint128::from(70u128)
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        let pool_amount = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&pool, "gWood"))
+            .unwrap();
+        assert_eq!(pool_amount, Uint128::from(30u128));
+
+        // only the floor is left, nothing moves and no sweep is recorded
+        for amount in [None, Some(Uint128::zero())] {
+            let sweep_msg = ExecuteMsg::TransferReserveAmount {
+                items: Some(vec!["gWood".to_string()]),
+                amount,
+            };
+            let res = execute(deps.as_mut(), mock_env(), minter.clone(), sweep_msg).unwrap();
+            assert!(res.messages.is_empty());
+            assert!(!res.attributes.iter().any(|attr| attr.key == "sweep_id"));
+        }
+        let res: ReserveSweepsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ReserveSweeps {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.sweeps.len(), 1);
+        assert_eq!(res.sweeps[0].id, 1);
+        assert_eq!(res.sweeps[0].items[0].amount, Uint128::from(70u128));
+    }
+
+    #[test]
+    fn test_claim_reward_with_short_pool() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 10u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), reward_msg).unwrap();
+        stake_tool(deps.as_mut(), "user1", &token_id);
+        let pool = mock_env().contract.address;
+        add_amount_in_item_address(&mut deps.storage, &pool, "gWood", Uint128::from(3u128))
+            .unwrap();
+
+        let res: PoolStatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PoolStatus {}).unwrap())
+                .unwrap();
+        let wood = res
+            .items
+            .iter()
+            .find(|item| item.item_name == "gWood")
+            .unwrap();
+        assert_eq!(wood.daily_emission, Uint128::from(8640u128));
+        assert_eq!(wood.runway_days, Some(0));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let claim_msg = ExecuteMsg::ClaimReward {
+            token_id: token_id.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &[]),
+            claim_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientPool {
+                item: "gWood".to_string(),
+                available: Uint128::from(3u128),
+                need: Uint128::from(10u128),
+            }
+        );
+
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            team_addr: None,
+            market_addr: None,
+            legal_addr: None,
+            burn_addr: None,
+            stake_limit: None,
+            durability_from_start_time: None,
+            reserve_addr: None,
+            pool_floor: None,
+            partial_claims: Some(true),
+            transfer_fee_bps: None,
+        });
+        execute(deps.as_mut(), mock_env(), minter, update_msg).unwrap();
+        execute(deps.as_mut(), env, mock_info("user1", &[]), claim_msg).unwrap();
+        let user_amount = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&Addr::unchecked("user1"), "gWood"))
+            .unwrap();
+        assert_eq!(user_amount, Uint128::from(3u128));
+    }
+
+    #[test]
+    fn test_claim_reward_credits_every_output() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 10u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: Some(vec![
+                RewardOutput {
+                    item_name: "gGold".to_string(),
+                    mining_rate: 4u64,
+                    chance_bps: 5000,
+                },
+                RewardOutput {
+                    item_name: "gStone".to_string(),
+                    mining_rate: 5u64,
+                    chance_bps: 0,
+                },
+            ]),
+        };
+        execute(deps.as_mut(), mock_env(), minter, reward_msg).unwrap();
+        stake_tool(deps.as_mut(), "user1", &token_id);
+        let pool = mock_env().contract.address;
+        add_amount_in_item_address(&mut deps.storage, &pool, "gWood", Uint128::from(10u128))
+            .unwrap();
+        add_amount_in_item_address(&mut deps.storage, &pool, "gGold", Uint128::from(1u128))
+            .unwrap();
+        add_amount_in_item_address(&mut deps.storage, &pool, "gStone", Uint128::from(5u128))
+            .unwrap();
+
+        let res: NftInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::NftInfo {
+                    token_id: token_id.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.extension.outputs.len(), 3);
+        assert_eq!(res.extension.outputs[0].item_name, "gWood");
+        assert_eq!(res.extension.outputs[0].chance_bps, 10000);
+
+        // half the gold rate is due but the pool is short so only what is left is paid,
+        // stone has no share and is never paid
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let claim_msg = ExecuteMsg::ClaimReward { token_id };
+        execute(deps.as_mut(), env, mock_info("user1", &[]), claim_msg).unwrap();
+        let user = Addr::unchecked("user1");
+        for (item_name, amount) in [("gWood", 10u128), ("gGold", 1u128)] {
+            let user_amount = USER_ITEM_AMOUNT
+                .load(&deps.storage, (&user, item_name))
+                .unwrap();
+            assert_eq!(user_amount, Uint128::from(amount));
+        }
+        assert!(USER_ITEM_AMOUNT
+            .may_load(&deps.storage, (&user, "gStone"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_emission_caps_limit_remaining_budget() {
+        let mut deps = mock_dependencies(&[]);
+        setup_contract(deps.as_mut());
+        let emission_config = EmissionConfig {
+            epoch_length: 86400,
+            global_cap: Some(Uint128::from(100u128)),
+            item_caps: vec![ItemEmissionCap {
+                item_name: "gWood".to_string(),
+                cap: Uint128::from(30u128),
+            }],
+        };
+        let now = mock_env().block.time.seconds();
+        let mut usage = load_emission_usage(&deps.storage, &emission_config, now).unwrap();
+        usage.record("gWood", Uint128::from(25u128));
+        usage.record("gFood", Uint128::from(55u128));
+        assert_eq!(
+            emission_config.remaining(&usage, "gWood"),
+            Some(Uint128::from(5u128))
+        );
+        assert_eq!(
+            emission_config.remaining(&usage, "gFood"),
+            Some(Uint128::from(20u128))
+        );
+        EMISSION_USAGE.save(&mut deps.storage, &usage).unwrap();
+
+        // a new epoch starts with the full budget
+        let usage = load_emission_usage(&deps.storage, &emission_config, now + 86400).unwrap();
+        assert_eq!(usage.total, Uint128::zero());
+        assert_eq!(
+            emission_config.remaining(&usage, "gWood"),
+            Some(Uint128::from(30u128))
+        );
+        // caps are off without an epoch length
+        let disabled = EmissionConfig::default();
+        assert_eq!(disabled.remaining(&usage, "gWood"), None);
+    }
+
+    #[test]
+    fn test_reward_rate_schedules() {
+        let mut reward_token = RewardToken {
+            item_name: "gWood".to_string(),
+            mining_rate: 100,
+            mining_waiting_time: 100,
+            schedule: Some(RateSchedule::Halvings {
+                at: vec![1000, 2000],
+            }),
+            extra_outputs: vec![],
+        };
+        assert_eq!(reward_token.rate_at(999), 100);
+        assert_eq!(reward_token.rate_at(1000), 50);
+        assert_eq!(reward_token.next_change(1000), Some((2000, 25)));
+        assert_eq!(reward_token.next_change(2000), None);
+
+        reward_token.schedule = Some(RateSchedule::Linear {
+            start: 0,
+            interval: 10,
+            amount: 30,
+            floor: 20,
+        });
+        assert_eq!(reward_token.rate_at(25), 40);
+        assert_eq!(reward_token.rate_at(30), 20);
+        assert_eq!(reward_token.next_change(30), None);
+
+        reward_token.schedule = Some(RateSchedule::Exponential {
+            start: 0,
+            interval: 10,
+            bps: 5000,
+            floor: 0,
+        });
+        assert_eq!(reward_token.rate_at(30), 12);
+        assert_eq!(reward_token.next_change(30), Some((40, 6)));
+
+        // a small rate rounds several steps flat, the next change skips past them
+        reward_token.mining_rate = 3;
+        reward_token.schedule = Some(RateSchedule::Exponential {
+            start: 0,
+            interval: 10,
+            bps: 9000,
+            floor: 0,
+        });
+        assert_eq!(reward_token.rate_at(10), 2);
+        assert_eq!(reward_token.rate_at(30), 2);
+        assert_eq!(reward_token.next_change(10), Some((40, 1)));
+        assert_eq!(reward_token.next_change(40), Some((110, 0)));
+        assert_eq!(reward_token.next_change(110), None);
+
+        reward_token.schedule = Some(RateSchedule::Exponential {
+            start: 0,
+            interval: 10,
+            bps: BPS_DENOMINATOR,
+            floor: 0,
+        });
+        assert_eq!(reward_token.next_change(10), None);
+
+        let invalid = RateSchedule::Halvings {
+            at: vec![2000, 1000],
+        };
+        assert!(!invalid.is_valid());
+    }
+
+    #[test]
+    fn test_rarity_multiplier_scales_nft_info() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 10u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), reward_msg).unwrap();
+        let multiplier_msg = ExecuteMsg::SetRarityMultiplier {
+            rarity: "Common".to_string(),
+            multiplier: RarityMultiplier {
+                mining_rate_bps: 15000,
+                waiting_time_bps: 5000,
+                durability_cost: 2,
+            },
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            multiplier_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), minter, multiplier_msg).unwrap();
+
+        let res: NftInfoResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::NftInfo { token_id }).unwrap())
+                .unwrap();
+        assert_eq!(res.extension.mining_rate, 15);
+        assert_eq!(res.extension.mining_waiting_time, 50);
+    }
+
+    #[test]
+    fn test_reward_tokens_keyed_by_template() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let template_msg = ExecuteMsg::AddToolTemplate(ToolTemplateMsg {
+            tool_type: "Stone Miner".to_string(),
+            name: "Chisel".to_string(),
+            description: "".to_string(),
+            image: "ipfs://Qmcnz2b3XkMsMwXLnAD5qXz9cGAHWRr74wyBFm1qB6UHQW".to_string(),
+            rarity: "Common".to_string(),
+            required_items: vec![],
+            durability: 10,
+            token_uri: None,
+        });
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), template_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RewardTokenNotFound {
+                tool_type: "Stone Miner".to_string(),
+                rarity: "Common".to_string(),
+            }
+        );
+
+        // a reward saved under the tool name before rewards were keyed by template
+        mint_axe(deps.as_mut(), &minter, "user1");
+        REWARD_TOKEN.remove(deps.as_mut().storage, ("Wood Miner", "Common"));
+        let legacy_reward = RewardToken {
+            item_name: "gWood".to_string(),
+            mining_rate: 7,
+            mining_waiting_time: 100,
+            schedule: None,
+            extra_outputs: vec![],
+        };
+        LEGACY_REWARD_TOKEN
+            .save(deps.as_mut().storage, "Axe".to_string(), &legacy_reward)
+            .unwrap();
+        // no template is named Hammer so its reward can not be attached
+        LEGACY_REWARD_TOKEN
+            .save(deps.as_mut().storage, "Hammer".to_string(), &legacy_reward)
+            .unwrap();
+
+        let res = migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                legacy_template_items: None,
+            },
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "dropped_rewards" && attr.value == "Hammer"));
+
+        let res: RewardTokensResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RewardTokens {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.reward_tokens.len(), 1);
+        assert_eq!(res.reward_tokens[0].tool_type, "Wood Miner");
+        assert_eq!(res.reward_tokens[0].rarity, "Common");
+        assert_eq!(res.reward_tokens[0].reward_token, legacy_reward);
+        assert!(LEGACY_REWARD_TOKEN
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_template_costs_use_item_registry() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        for (item_name, token_addr) in [("gWood", "woodaddr"), ("gFood", "foodaddr")] {
+            let add_item_msg = ExecuteMsg::AddItemToken {
+                item_name: item_name.to_string(),
+                item_token_addr: token_addr.to_string(),
+                decimals: None,
+            };
+            execute(deps.as_mut(), mock_env(), minter.clone(), add_item_msg).unwrap();
+        }
+        let reward_msg = ExecuteMsg::AddRewardToken {
+            item_name: "gWood".to_string(),
+            tool_type: "Wood Miner".to_string(),
+            rarity: "Common".to_string(),
+            mining_rate: 100u64,
+            mining_waiting_time: 100u64,
+            schedule: None,
+            extra_outputs: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), reward_msg).unwrap();
+        let template_msg = |required_items: Vec<(&str, u128)>| {
+            ExecuteMsg::AddToolTemplate(ToolTemplateMsg {
+                tool_type: "Wood Miner".to_string(),
+                name: "Axe".to_string(),
+                description: "".to_string(),
+                image: "".to_string(),
+                rarity: "Common".to_string(),
+                required_items: required_items
+                    .into_iter()
+                    .map(|(item_name, amount)| (item_name.to_string(), Uint128::from(amount)))
+                    .collect(),
+                durability: 10,
+                token_uri: None,
+            })
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            template_msg(vec![("gWood", 5), ("gGold", 1)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ItemTokenNotFound {
+                item: "gGold".to_string()
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            template_msg(vec![("gWood", 5), ("gWood", 1)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateItem {
+                item: "gWood".to_string()
+            }
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            template_msg(vec![("gWood", 5), ("gFood", 2)]),
+        )
+        .unwrap();
+
+        let user = Addr::unchecked("user1");
+        add_amount_in_item_address(&mut deps.storage, &user, "gWood", Uint128::from(5u128))
+            .unwrap();
+        add_amount_in_item_address(&mut deps.storage, &user, "gFood", Uint128::from(3u128))
+            .unwrap();
+        let mint_msg = ExecuteMsg::MintCommonNft {
+            tool_type: "Wood Miner".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), mint_msg).unwrap();
+        for (item_name, amount) in [("gWood", 0u128), ("gFood", 1u128)] {
+            let user_amount = USER_ITEM_AMOUNT
+                .load(&deps.storage, (&user, item_name))
+                .unwrap();
+            assert_eq!(user_amount, Uint128::from(amount));
+        }
+    }
+
+    #[test]
+    fn test_item_lifecycle() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        for (item_name, token_addr) in [("gWood", "woodaddr"), ("gFood", "foodaddr")] {
+            let add_item_msg = ExecuteMsg::AddItem {
+                item_name: item_name.to_string(),
+                item_token_addr: token_addr.to_string(),
+                decimals: None,
+            };
+            execute(deps.as_mut(), mock_env(), minter.clone(), add_item_msg).unwrap();
+        }
+        let add_item_msg = ExecuteMsg::AddItem {
+            item_name: "gWood".to_string(),
+            item_token_addr: "otheraddr".to_string(),
+            decimals: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), add_item_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateItem {
+                item: "gWood".to_string()
+            }
+        );
+
+        // the reward of the axe mines gWood so it can only be disabled
+        mint_axe(deps.as_mut(), &minter, "user1");
+        let remove_msg = ExecuteMsg::RemoveItem {
+            item_name: "gWood".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), remove_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ItemInUse {
+                item: "gWood".to_string(),
+                used_by: "reward Wood Miner Common".to_string(),
+            }
+        );
+        let disable_msg = ExecuteMsg::DisableItem {
+            item_name: "gWood".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), minter.clone(), disable_msg).unwrap();
+        assert_eq!(res.events[0].ty, "item_disabled");
+        let template_msg = ExecuteMsg::AddToolTemplate(ToolTemplateMsg {
+            tool_type: "Wood Miner".to_string(),
+            name: "Axe".to_string(),
+            description: "".to_string(),
+            image: "".to_string(),
+            rarity: "Common".to_string(),
+            required_items: vec![("gWood".to_string(), Uint128::from(5u128))],
+            durability: 10,
+            token_uri: None,
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            template_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ItemDisabled {
+                item: "gWood".to_string()
+            }
+        );
+        let enable_msg = ExecuteMsg::EnableItem {
+            item_name: "gWood".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), minter.clone(), enable_msg).unwrap();
+        assert_eq!(res.events[0].ty, "item_enabled");
+        execute(deps.as_mut(), mock_env(), minter.clone(), template_msg).unwrap();
+
+        // the legacy entry point can not map a token that backs another item
+        let add_token_msg = ExecuteMsg::AddItemToken {
+            item_name: "gMeat".to_string(),
+            item_token_addr: "foodaddr".to_string(),
+            decimals: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), add_token_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateItem {
+                item: "gFood".to_string()
+            }
+        );
+        let add_token_msg = ExecuteMsg::AddItemToken {
+            item_name: "gWood".to_string(),
+            item_token_addr: "foodaddr".to_string(),
+            decimals: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), add_token_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateItem {
+                item: "gFood".to_string()
+            }
+        );
+
+        // gFood refills energy, has a withdraw policy and a balance until each is dropped
+        let remove_msg = ExecuteMsg::RemoveItem {
+            item_name: "gFood".to_string(),
+        };
+        let policy_msg = ExecuteMsg::SetWithdrawPolicy {
+            item_name: "gFood".to_string(),
+            policy: WithdrawPolicy {
+                limit: None,
+                window: 0,
+                fee_bps: 100,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), policy_msg).unwrap();
+        let user = Addr::unchecked("user1");
+        add_amount_in_item_address(&mut deps.storage, &user, "gFood", Uint128::from(5u128))
+            .unwrap();
+        let in_use = |used_by: &str| ContractError::ItemInUse {
+            item: "gFood".to_string(),
+            used_by: used_by.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            remove_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, in_use("energy refill"));
+        let energy_msg = ExecuteMsg::UpdateEnergyConfig(UpdateEnergyConfigMsg {
+            starter_energy: None,
+            claim_cost: None,
+            claim_cost_overrides: None,
+            refill_item: Some("gWood".to_string()),
+            refill_ratio: None,
+            max_energy: None,
+            regen_amount: None,
+            regen_interval: None,
+        });
+        execute(deps.as_mut(), mock_env(), minter.clone(), energy_msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            remove_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, in_use("withdraw policy"));
+        let remove_policy_msg = ExecuteMsg::RemoveWithdrawPolicy {
+            item_name: "gFood".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), remove_policy_msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            remove_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, in_use("balances"));
+        let withdraw_msg = ExecuteMsg::Withdraw {
+            item_name: "gFood".to_string(),
+            amount: Uint128::from(5u128),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            withdraw_msg,
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), minter.clone(), remove_msg).unwrap();
+        let game_dev_tokens: Vec<String> =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryGameDevToken {}).unwrap())
+                .unwrap();
+        assert_eq!(game_dev_tokens, vec!["gWood".to_string()]);
+    }
+
+    #[test]
+    fn test_transfer_items_with_fee() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            team_addr: None,
+            market_addr: None,
+            legal_addr: None,
+            burn_addr: None,
+            stake_limit: None,
+            durability_from_start_time: None,
+            reserve_addr: None,
+            pool_floor: None,
+            partial_claims: None,
+            transfer_fee_bps: Some(1000),
+        });
+        execute(deps.as_mut(), mock_env(), minter, update_msg).unwrap();
+        let user1 = Addr::unchecked("user1");
+        let user2 = Addr::unchecked("user2");
+        add_amount_in_item_address(&mut deps.storage, &user1, "gWood", Uint128::from(100u128))
+            .unwrap();
+        add_amount_in_item_address(&mut deps.storage, &user1, "gFood", Uint128::from(10u128))
+            .unwrap();
+
+        let transfer_msg = ExecuteMsg::TransferItem {
+            recipient: "user2".to_string(),
+            item_name: "gWood".to_string(),
+            amount: Uint128::from(50u128),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+        let received = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&user2, "gWood"))
+            .unwrap();
+        assert_eq!(received, Uint128::from(45u128));
+        let config = CONFIG.load(&deps.storage).unwrap();
+        let burned = PENDING_BURNS.load(&deps.storage, "gWood").unwrap();
+        let fees = default_distribution().iter().fold(burned, |fees, share| {
+            let address = share.recipient.address(&config, &mock_env());
+            fees + USER_ITEM_AMOUNT
+                .may_load(&deps.storage, (&address, "gWood"))
+                .unwrap()
+                .unwrap_or_default()
+        });
+        assert_eq!(fees, Uint128::from(5u128));
+
+        // a batch fails as a whole when one of its items is short
+        let batch_msg = ExecuteMsg::SendItemBatch {
+            recipient: "user2".to_string(),
+            items: vec![
+                ItemAmount {
+                    item_name: "gWood".to_string(),
+                    amount: Uint128::from(50u128),
+                },
+                ItemAmount {
+                    item_name: "gFood".to_string(),
+                    amount: Uint128::from(20u128),
+                },
+            ],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            batch_msg,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientItem {
+                item: "gFood".to_string(),
+                have: Uint128::from(10u128),
+                need: Uint128::from(20u128),
+            }
+        );
+    }
+
+    #[test]
+    fn test_withdraw_limit_and_fee() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_token_msg = ExecuteMsg::AddItemToken {
+            item_token_addr: "woodaddr".to_string(),
+            item_name: "gWood".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), add_token_msg).unwrap();
+        let policy_msg = ExecuteMsg::SetWithdrawPolicy {
+            item_name: "gWood".to_string(),
+            policy: WithdrawPolicy {
+                limit: Some(Uint128::from(100u128)),
+                window: 3600,
+                fee_bps: 1000,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), minter, policy_msg).unwrap();
+        let user = Addr::unchecked("user1");
+        add_amount_in_item_address(&mut deps.storage, &user, "gWood", Uint128::from(300u128))
+            .unwrap();
+
+        let withdraw_msg = |amount: u128| ExecuteMsg::Withdraw {
+            item_name: "gWood".to_string(),
+            amount: Uint128::from(amount),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            withdraw_msg(80),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "woodaddr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "user1".to_string(),
+                    amount: Uint128::from(72u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let now = mock_env().block.time.seconds();
+        let res: WithdrawAllowanceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::WithdrawAllowance {
+                    address: "user1".to_string(),
+                    item_name: "gWood".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.remaining, Some(Uint128::from(20u128)));
+        assert_eq!(res.frees_at, Some(now + 3600));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            withdraw_msg(30),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WithdrawLimitReached {
+                item: "gWood".to_string(),
+                remaining: Uint128::from(20u128),
+                frees_at: now + 3600,
+            }
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1800);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &[]),
+            withdraw_msg(20),
+        )
+        .unwrap();
+
+        // the window rolls, only the first withdrawal has left it an hour later
+        env.block.time = env.block.time.plus_seconds(1800);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &[]),
+            withdraw_msg(90),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WithdrawLimitReached {
+                item: "gWood".to_string(),
+                remaining: Uint128::from(80u128),
+                frees_at: now + 5400,
+            }
+        );
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info("user1", &[]),
+            withdraw_msg(80),
+        )
+        .unwrap();
+
+        // the whole amount as fee would leave nothing to transfer
+        let policy_msg = ExecuteMsg::SetWithdrawPolicy {
+            item_name: "gWood".to_string(),
+            policy: WithdrawPolicy {
+                limit: None,
+                window: 0,
+                fee_bps: 10000,
+            },
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            policy_msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidWithdrawPolicy {});
+
+        // a window past the end of time keeps withdrawals in it instead of overflowing
+        let policy = WithdrawPolicy {
+            limit: Some(Uint128::from(100u128)),
+            window: u64::MAX,
+            fee_bps: 0,
+        };
+        let mut usage = policy.current_usage(None, 1000);
+        usage.record(1000, Uint128::from(10u128));
+        assert_eq!(policy.frees_at(&usage), Some(u64::MAX));
+        assert_eq!(policy.current_usage(Some(usage.clone()), 2000), usage);
+    }
+
+    #[test]
+    fn test_native_item_deposit_and_withdraw() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_item_msg = ExecuteMsg::AddNativeItem {
+            item_name: "gGem".to_string(),
+            denom: "ugem".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), add_item_msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            ExecuteMsg::AddItemToken {
+                item_name: "gGem".to_string(),
+                item_token_addr: "gem_token".to_string(),
+                decimals: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NativeItem {
+                item: "gGem".to_string()
+            }
+        );
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &coins(10, "uluna")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownDenom {
+                denom: "uluna".to_string()
+            }
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &coins(50, "ugem")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+        let user = Addr::unchecked("user1");
+        let user_amount = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&user, "gGem"))
+            .unwrap();
+        assert_eq!(user_amount, Uint128::from(50u128));
+
+        let withdraw_msg = ExecuteMsg::Withdraw {
+            item_name: "gGem".to_string(),
+            amount: Uint128::from(20u128),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            withdraw_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user1".to_string(),
+                amount: coins(20, "ugem"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_registered_items_are_the_default_item_list() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_token_msg = ExecuteMsg::AddItemToken {
+            item_token_addr: "woodaddr".to_string(),
+            item_name: "gWood".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), add_token_msg).unwrap();
+        let team = Addr::unchecked("team_address");
+        let pool = mock_env().contract.address;
+        add_amount_in_item_address(&mut deps.storage, &team, "gWood", Uint128::from(7u128))
+            .unwrap();
+        add_amount_in_item_address(&mut deps.storage, &pool, "gWood", Uint128::from(30u128))
+            .unwrap();
+        let transfer = |recipient: &str, amount: u128| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "woodaddr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::from(amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+
+        let payout_msg = ExecuteMsg::PayoutStakeholders { items: None };
+        let res = execute(deps.as_mut(), mock_env(), minter.clone(), payout_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, transfer("team_address", 7));
+
+        let sweep_msg = ExecuteMsg::TransferReserveAmount {
+            items: None,
+            amount: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), minter, sweep_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, transfer("reserve_address", 30));
+    }
+
+    #[test]
+    fn test_cw721_queries() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let first_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let second_id = mint_axe(deps.as_mut(), &minter, "owner");
+        let third_id = mint_axe(deps.as_mut(), &minter, "owner");
+
+        let res: MinterResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Minter {}).unwrap()).unwrap();
+        assert_eq!(res.minter, "minter");
+
+        let res: TokensResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllTokens {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let mut all = vec![first_id.clone(), second_id.clone(), third_id.clone()];
+        all.sort();
+        assert_eq!(res.tokens, all);
+
+        // pages continue after the given token id
+        let res: TokensResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllTokens {
+                    start_after: Some(all[0].clone()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.tokens, vec![all[1].clone()]);
+        let res: TokensResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllTokens {
+                    start_after: Some(all[1].clone()),
+                    limit: Some(5),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.tokens, vec![all[2].clone()]);
+
+        // an approval that expires at the next block height
+        let env = mock_env();
+        let approve_msg = ExecuteMsg::Approve {
+            spender: "spender".to_string(),
+            token_id: first_id.clone(),
+            expires: Some(Expiration::AtHeight(env.block.height + 1)),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            approve_msg,
+        )
+        .unwrap();
+
+        let res: ApprovalsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Approvals {
+                    token_id: first_id.clone(),
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.approvals.len(), 1);
+
+        let mut later = env;
+        later.block.height += 1;
+        let res: ApprovalsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                later.clone(),
+                QueryMsg::Approvals {
+                    token_id: first_id.clone(),
+                    include_expired: Some(false),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(res.approvals.is_empty());
+        let res: ApprovalsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                later.clone(),
+                QueryMsg::Approvals {
+                    token_id: first_id.clone(),
+                    include_expired: Some(true),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.approvals[0].spender, "spender");
+
+        let err = query(
+            deps.as_ref(),
+            later.clone(),
+            QueryMsg::Approval {
+                token_id: first_id.clone(),
+                spender: "spender".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ApprovalNotFound {
+                spender: "spender".to_string()
+            }
+        );
+        let res: ApprovalResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                later,
+                QueryMsg::Approval {
+                    token_id: first_id,
+                    spender: "spender".to_string(),
+                    include_expired: Some(true),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.approval.spender, "spender");
+    }
+
+    #[test]
+    fn test_refill_shares_are_paid_to_stakeholders() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let add_item_msg = ExecuteMsg::AddItem {
+            item_name: "gFood".to_string(),
+            item_token_addr: "foodaddr".to_string(),
+            decimals: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), add_item_msg).unwrap();
+        add_amount_in_item_address(
+            deps.as_mut().storage,
+            &Addr::unchecked("user1"),
+            "gFood",
+            Uint128::from(100u128),
+        )
+        .unwrap();
+        let refill_msg = ExecuteMsg::RefillEnergy {
+            food_item_amount: 100,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            refill_msg,
+        )
+        .unwrap();
+
+        let expected = [
+            ("team_address", 10u128),
+            ("market_address", 10u128),
+            ("legal_address", 5u128),
+        ];
+        let res: StakeholderBalancesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::StakeholderBalances { items: None },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.stakeholders,
+            expected
+                .iter()
+                .map(|(address, amount)| StakeholderBalance {
+                    address: address.to_string(),
+                    items: vec![ItemAmount {
+                        item_name: "gFood".to_string(),
+                        amount: Uint128::from(*amount),
+                    }],
+                })
+                .collect::<Vec<_>>()
+        );
+
+        let payout_msg = ExecuteMsg::PayoutStakeholders { items: None };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            payout_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), minter, payout_msg).unwrap();
+        assert_eq!(res.messages.len(), expected.len());
+        for (message, (address, amount)) in res.messages.iter().zip(expected.iter()) {
+            assert_eq!(
+                message.msg,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "foodaddr".to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: address.to_string(),
+                        amount: Uint128::from(*amount)
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                })
+            );
+        }
+
+        for (address, _) in expected.iter() {
+            assert_eq!(
+                USER_ITEM_AMOUNT
+                    .may_load(&deps.storage, (&Addr::unchecked(*address), "gFood"))
+                    .unwrap()
+                    .unwrap_or_default(),
+                Uint128::zero()
+            );
+        }
+        let res: StakeholderBalancesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::StakeholderBalances { items: None },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(res
+            .stakeholders
+            .iter()
+            .all(|stakeholder| stakeholder.items.is_empty()));
+    }
+
+    #[test]
+    fn test_config_and_registry_queries() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let env = mock_env();
+
+        let config: Config =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                minter: "minter".to_string(),
+                team_addr: "team_address".to_string(),
+                market_addr: "market_address".to_string(),
+                legal_addr: "legal_address".to_string(),
+                burn_addr: "burn_address".to_string(),
+                stake_limit: 20,
+                durability_start_time: env.block.time.seconds() + 2592000,
+                reserve_addr: "reserve_address".to_string(),
+                repair_kit_waiting_time: 360,
+                pool_floor: Uint128::zero(),
+                partial_claims: false,
+                transfer_fee_bps: 0,
+            }
+        );
+        let energy_config: EnergyConfig =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EnergyConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(energy_config, EnergyConfig::default());
+        let emission_config: EmissionConfig =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EmissionConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(emission_config, EmissionConfig::default());
+
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            team_addr: Some("new_team".to_string()),
+            market_addr: None,
+            legal_addr: None,
+            burn_addr: None,
+            stake_limit: Some(5),
+            durability_from_start_time: None,
+            reserve_addr: None,
+            pool_floor: Some(Uint128::from(1000u128)),
+            partial_claims: Some(true),
+            transfer_fee_bps: Some(100),
+        });
+        execute(deps.as_mut(), env.clone(), minter.clone(), update_msg).unwrap();
+        let update_msg = ExecuteMsg::UpdateEnergyConfig(UpdateEnergyConfigMsg {
+            starter_energy: Some(Uint128::from(50u128)),
+            claim_cost: None,
+            claim_cost_overrides: None,
+            refill_item: None,
+            refill_ratio: Some(Uint128::from(2u128)),
+            max_energy: Some(Uint128::from(400u128)),
+            regen_amount: None,
+            regen_interval: None,
+        });
+        execute(deps.as_mut(), env.clone(), minter.clone(), update_msg).unwrap();
+        let item_caps = vec![ItemEmissionCap {
+            item_name: "gWood".to_string(),
+            cap: Uint128::from(500u128),
+        }];
+        let update_msg = ExecuteMsg::UpdateEmissionConfig(UpdateEmissionConfigMsg {
+            epoch_length: Some(86400),
+            global_cap: Some(Uint128::from(1000u128)),
+            item_caps: Some(item_caps.clone()),
+        });
+        execute(deps.as_mut(), env.clone(), minter.clone(), update_msg).unwrap();
+        let gold_shares = vec![
+            DistributionShare {
+                recipient: DistributionRecipient::Team,
+                bps: 2000,
+            },
+            DistributionShare {
+                recipient: DistributionRecipient::Pool,
+                bps: 8000,
+            },
+        ];
+        let distribution_msg = ExecuteMsg::SetDistribution {
+            item_name: Some("gGold".to_string()),
+            shares: gold_shares.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), minter.clone(), distribution_msg).unwrap();
+        let add_item_msg = ExecuteMsg::AddItem {
+            item_name: "gWood".to_string(),
+            item_token_addr: "woodaddr".to_string(),
+            decimals: Some(8),
+        };
+        execute(deps.as_mut(), env.clone(), minter, add_item_msg).unwrap();
+
+        let config: Config =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.team_addr, "new_team");
+        assert_eq!(config.market_addr, "market_address");
+        assert_eq!(config.stake_limit, 5);
+        assert_eq!(config.pool_floor, Uint128::from(1000u128));
+        assert!(config.partial_claims);
+        assert_eq!(config.transfer_fee_bps, 100);
+
+        let energy_config: EnergyConfig =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EnergyConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            energy_config,
+            EnergyConfig {
+                starter_energy: Uint128::from(50u128),
+                refill_ratio: Uint128::from(2u128),
+                max_energy: Some(Uint128::from(400u128)),
+                ..EnergyConfig::default()
+            }
+        );
+
+        let emission_config: EmissionConfig =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EmissionConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            emission_config,
+            EmissionConfig {
+                epoch_length: 86400,
+                global_cap: Some(Uint128::from(1000u128)),
+                item_caps,
+            }
+        );
+
+        let res: DistributionResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Distribution {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.shares, default_distribution());
+        assert_eq!(
+            res.item_overrides,
+            vec![ItemDistributionInfo {
+                item_name: "gGold".to_string(),
+                shares: gold_shares,
+            }]
+        );
+
+        let res: ItemTokensResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::ItemTokens {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.items,
+            vec![ItemTokenInfo {
+                item_name: "gWood".to_string(),
+                token_addr: "woodaddr".to_string(),
+                decimals: 8,
+                enabled: true,
+                native: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stake_limit_and_batch_errors() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            team_addr: None,
+            market_addr: None,
+            legal_addr: None,
+            burn_addr: None,
+            stake_limit: Some(2),
+            durability_from_start_time: None,
+            reserve_addr: None,
+            pool_floor: None,
+            partial_claims: None,
+            transfer_fee_bps: None,
+        });
+        execute(deps.as_mut(), mock_env(), minter.clone(), update_msg).unwrap();
+        let first_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let second_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let third_id = mint_axe(deps.as_mut(), &minter, "user1");
+        stake_tool(deps.as_mut(), "user1", &first_id);
+        stake_tool(deps.as_mut(), "user1", &second_id);
+
+        // the limit is the number of tools a user can have staked
+        let contract_addr = mock_env().contract.address.to_string();
+        let hook = to_binary(&Cw721HookMsg::Stake {}).unwrap();
+        let send_msg = ExecuteMsg::SendNft {
+            contract: contract_addr.clone(),
+            token_id: third_id.clone(),
+            msg: hook.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), send_msg).unwrap();
+        let hook_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "user1".to_string(),
+            token_id: third_id,
+            msg: hook,
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&contract_addr, &[]),
+            hook_msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::StakeLimitReached { limit: 2 });
+
+        // tools still mining are reported, an unknown token fails the batch
+        let claim_msg = ExecuteMsg::ClaimRewards {
+            token_ids: Some(vec![first_id.clone()]),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            claim_msg,
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "claimed" && attr.value == "0"));
+        let claim_msg = ExecuteMsg::ClaimRewards {
+            token_ids: Some(vec![first_id.clone(), "999".to_string()]),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            claim_msg,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokenNotFound {
+                token_id: "999".to_string()
+            }
+        );
+        let unstake_msg = ExecuteMsg::UnstakeMany {
+            token_ids: vec![first_id.clone(), second_id],
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user1", &[]),
+            unstake_msg,
+        )
+        .unwrap();
+        let result = |key: String| {
+            res.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(result("unstaked".to_string()), "0");
+        assert_eq!(
+            result(format!("token_{}", first_id)),
+            ContractError::MiningNotReady {
+                token_id: first_id,
+                ready_at: mock_env().block.time.seconds() + 100,
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_repair_charges_the_fee() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let token_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let kit_id = mint_axe(deps.as_mut(), &minter, "user1");
+        REPAIRING_FEE
+            .save(
+                &mut deps.storage,
+                "Wood Miner".to_string(),
+                &Uint128::from(20u128),
+            )
+            .unwrap();
+        let mut token = tokens().load(&deps.storage, &token_id).unwrap();
+        token.durability = 2;
+        tokens().save(&mut deps.storage, &token_id, &token).unwrap();
+        let user = Addr::unchecked("user1");
+        add_amount_in_item_address(&mut deps.storage, &user, "gWood", Uint128::from(50u128))
+            .unwrap();
+
+        let contract_addr = mock_env().contract.address.to_string();
+        let hook = to_binary(&Cw721HookMsg::StakeRepairKit {}).unwrap();
+        let send_msg = ExecuteMsg::SendNft {
+            contract: contract_addr.clone(),
+            token_id: kit_id.clone(),
+            msg: hook.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), send_msg).unwrap();
+        let hook_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "user1".to_string(),
+            token_id: kit_id,
+            msg: hook,
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&contract_addr, &[]),
+            hook_msg,
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(361);
+        let repair_msg = ExecuteMsg::UseRepairKit {
+            token_id: token_id.clone(),
+        };
+        execute(deps.as_mut(), env, mock_info("user1", &[]), repair_msg).unwrap();
+        let token = tokens().load(&deps.storage, &token_id).unwrap();
+        assert_eq!(token.durability, 10);
+        let user_amount = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&user, "gWood"))
+            .unwrap();
+        assert_eq!(user_amount, Uint128::from(30u128));
+        // the fee is split from what the user paid, half of it goes to the pool
+        let pool_amount = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&Addr::unchecked(contract_addr), "gWood"))
+            .unwrap();
+        assert_eq!(pool_amount, Uint128::from(10u128));
+    }
+
+    #[test]
+    fn test_claim_rewards_reports_broken_tools() {
+        let mut deps = mock_dependencies(&[]);
+        let minter = setup_contract(deps.as_mut());
+        let healthy_id = mint_axe(deps.as_mut(), &minter, "user1");
+        let broken_id = mint_axe(deps.as_mut(), &minter, "user1");
+        stake_tool(deps.as_mut(), "user1", &healthy_id);
+        stake_tool(deps.as_mut(), "user1", &broken_id);
+        let mut token = tokens().load(&deps.storage, &broken_id).unwrap();
+        token.durability = 0;
+        tokens()
+            .save(&mut deps.storage, &broken_id, &token)
+            .unwrap();
+        let pool = mock_env().contract.address;
+        add_amount_in_item_address(&mut deps.storage, &pool, "gWood", Uint128::from(100u128))
+            .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let claim_msg = ExecuteMsg::ClaimRewards { token_ids: None };
+        let res = execute(deps.as_mut(), env, mock_info("user1", &[]), claim_msg).unwrap();
+        let result = |key: String| {
+            res.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(result("claimed".to_string()), "1");
+        assert_eq!(result(format!("token_{}", healthy_id)), "claimed 100 gWood");
+        assert_eq!(
+            result(format!("token_{}", broken_id)),
+            ContractError::NeedsRepair {
+                token_id: broken_id.clone()
+            }
+            .to_string()
+        );
+        let user_amount = USER_ITEM_AMOUNT
+            .load(&deps.storage, (&Addr::unchecked("user1"), "gWood"))
+            .unwrap();
+        assert_eq!(user_amount, Uint128::from(100u128));
+    }
+}